@@ -0,0 +1,242 @@
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use rand::{Rng, RngCore};
+use rand::seq::SliceRandom;
+
+use crate::base::runner::{Listener, Runner};
+use crate::base::scale::scale_count;
+use crate::base::tests_supply::{Test, TestsNode, TestsSupplier};
+use crate::base::workdir::WorkDir;
+
+/// Room for code tables and headers which small files cannot amortize
+const HEADER_ALLOWANCE: usize = 1024;
+
+#[derive(Clone)]
+pub struct ArchiveInput {
+    kind: &'static str,
+    data: Vec<u8>,
+}
+
+impl Display for ArchiveInput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}, {} bytes", self.kind, self.data.len())?;
+        let preview: Vec<String> = self.data.iter().take(32).map(|a| format!("{:02x}", a)).collect();
+        write!(f, "\nFirst bytes: {}", preview.join(" "))
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub enum ArchiveOutput {
+    Expected { size: usize, max_ratio: Option<f64> },
+    Archived { restored: Vec<u8>, compressed_size: usize },
+    Failed(String),
+}
+
+impl Display for ArchiveOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArchiveOutput::Expected { size, max_ratio } => {
+                write!(f, "{} bytes restored exactly", size)?;
+                if let Some(ratio) = max_ratio {
+                    write!(f, ", compression ratio not above {:.2}", ratio)?;
+                }
+                Ok(())
+            }
+            ArchiveOutput::Archived { restored, compressed_size } => {
+                write!(f, "compressed to {} bytes, restored {} bytes", compressed_size, restored.len())
+            }
+            ArchiveOutput::Failed(e) => f.write_str(e),
+        }
+    }
+}
+
+impl Eq for ArchiveOutput {}
+
+pub fn check(input: &ArchiveInput, expected: &ArchiveOutput, actual: &ArchiveOutput) -> Result<(), String> {
+    let (restored, compressed_size) = match actual {
+        ArchiveOutput::Archived { restored, compressed_size } => (restored, *compressed_size),
+        ArchiveOutput::Failed(e) => return Err(e.clone()),
+        ArchiveOutput::Expected { .. } => return Err("Runner produced no archive".to_string()),
+    };
+    let max_ratio = match expected {
+        ArchiveOutput::Expected { max_ratio, .. } => *max_ratio,
+        _ => None,
+    };
+
+    if let Some(idx) = input.data.iter().zip(restored.iter()).position(|(a, b)| a != b) {
+        return Err(format!(
+            "Restored file differs at byte {}: expected {:02x}, found {:02x}", idx, input.data[idx], restored[idx]
+        ));
+    }
+    if input.data.len() != restored.len() {
+        return Err(format!("Restored file has {} bytes instead of {}", restored.len(), input.data.len()));
+    }
+
+    if let Some(max_ratio) = max_ratio {
+        let ratio = compressed_size as f64 / input.data.len() as f64;
+        if compressed_size > (max_ratio * input.data.len() as f64) as usize + HEADER_ALLOWANCE {
+            return Err(format!("Compression ratio {:.3} exceeds threshold {:.3}", ratio, max_ratio));
+        }
+    }
+
+    Ok(())
+}
+
+/// Ratio closest to its threshold is shown on the chunk line, passed tests included
+pub fn ratio_note(test: &Test<ArchiveInput, ArchiveOutput>, output: &ArchiveOutput) -> Option<(f64, String)> {
+    match (output, &test.output) {
+        (ArchiveOutput::Archived { compressed_size, .. }, ArchiveOutput::Expected { size, max_ratio: Some(max_ratio) }) => {
+            let ratio = *compressed_size as f64 / (*size).max(1) as f64;
+            Some((ratio / max_ratio, format!("ratio up to {:.3} (threshold {:.2})", ratio, max_ratio)))
+        }
+        _ => None,
+    }
+}
+
+/// Runs `<executable> c <file> <archive>` and then `<executable> d <archive> <file>` for every test
+pub struct ArchiverRunner {
+    file: PathBuf,
+}
+
+impl ArchiverRunner {
+    pub fn new(file: PathBuf) -> Self {
+        ArchiverRunner { file }
+    }
+
+    fn run_step(&self, mode: &str, from: &Path, to: &Path) -> Result<(), String> {
+        let output = Command::new(&self.file)
+            .arg(mode)
+            .arg(from)
+            .arg(to)
+            .output()
+            .map_err(|a| format!("Cannot start process: {}", a))?;
+        if !output.status.success() {
+            return Err(format!(
+                "'{}' step exited with {}: {}",
+                mode, output.status, String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+
+    fn round_trip(&self, dir: &WorkDir, input: &ArchiveInput) -> Result<ArchiveOutput, String> {
+        let original = dir.file("original");
+        let archive = dir.file("archive");
+        let restored = dir.file("restored");
+        std::fs::write(&original, &input.data).map_err(|a| format!("Cannot write test file: {}", a))?;
+
+        if let Err(e) = self.run_step("c", &original, &archive) {
+            return Ok(ArchiveOutput::Failed(e));
+        }
+        let compressed_size = match std::fs::metadata(&archive) {
+            Ok(metadata) => metadata.len() as usize,
+            Err(e) => return Ok(ArchiveOutput::Failed(format!("Cannot read archive: {}", e))),
+        };
+        if let Err(e) = self.run_step("d", &archive, &restored) {
+            return Ok(ArchiveOutput::Failed(e));
+        }
+        match std::fs::read(&restored) {
+            Ok(restored) => Ok(ArchiveOutput::Archived { restored, compressed_size }),
+            Err(e) => Ok(ArchiveOutput::Failed(format!("Cannot read restored file: {}", e))),
+        }
+    }
+}
+
+impl Runner<ArchiveInput, ArchiveOutput> for ArchiverRunner {
+    fn run(&self, input: &[Test<ArchiveInput, ArchiveOutput>], listener: &mut Listener<ArchiveInput, ArchiveOutput>) -> Result<(), String> {
+        let dir = WorkDir::new()?;
+        for test in input {
+            let output = self.round_trip(&dir, &test.input)?;
+            if !listener(test, &output, None) {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+const WORDS: [&str; 16] = [
+    "the", "quick", "brown", "fox", "jumps", "over", "lazy", "dog",
+    "lorem", "ipsum", "dolor", "sit", "amet", "huffman", "tree", "code",
+];
+
+fn make_test(kind: &'static str, data: Vec<u8>, max_ratio: Option<f64>) -> Test<ArchiveInput, ArchiveOutput> {
+    let expected = ArchiveOutput::Expected { size: data.len(), max_ratio };
+    Test::new(ArchiveInput { kind, data }, expected)
+}
+
+fn random_text(rng: &mut dyn RngCore, size: usize) -> Vec<u8> {
+    let mut text = Vec::with_capacity(size + 16);
+    while text.len() < size {
+        text.extend_from_slice(WORDS.choose(rng).unwrap().as_bytes());
+        text.push(if rng.gen_bool(0.1) { b'\n' } else { b' ' });
+    }
+    text.truncate(size);
+    text
+}
+
+pub struct ArchiverSupplier {
+    scale: f64,
+}
+
+impl ArchiverSupplier {
+    pub fn new(scale: f64) -> ArchiverSupplier {
+        ArchiverSupplier { scale }
+    }
+
+    fn hand_made(&self, node: &mut TestsNode<ArchiveInput, ArchiveOutput>) {
+        node.child("empty")
+            .add_test(make_test("empty file", Vec::new(), None));
+        node.child("single symbol")
+            .add_test(make_test("single byte", vec![b'a'], None))
+            .add_test(make_test("single symbol", vec![0; 1000], None))
+            .add_test(make_test("single symbol", vec![0xff; 100_000], Some(0.2)));
+        node.child("two symbols")
+            .add_test(make_test("alternating symbols", b"ab".repeat(5000), Some(0.2)));
+        node.child("all bytes")
+            .add_test(make_test("every byte value", (0..=255).collect(), None))
+            .add_test(make_test("every byte value repeated", (0..=255).cycle().take(256 * 100).collect(), Some(1.05)));
+    }
+}
+
+impl TestsSupplier<ArchiveInput, ArchiveOutput> for ArchiverSupplier {
+    fn supply_tests(&self, rng: &mut dyn RngCore) -> TestsNode<ArchiveInput, ArchiveOutput> {
+        let mut result = TestsNode::new("root");
+
+        self.hand_made(result.child("hand-made"));
+
+        let node = result.child("random bytes");
+        for _ in 0..scale_count(30, self.scale) {
+            let size = rng.gen_range(1..20_000);
+            let data = (0..size).map(|_| rng.gen::<u8>()).collect();
+            node.add_test(make_test("random bytes", data, Some(1.05)));
+        }
+
+        let node = result.child("text");
+        for _ in 0..scale_count(30, self.scale) {
+            let size = rng.gen_range(100..20_000);
+            node.add_test(make_test("text", random_text(rng, size), Some(0.8)));
+        }
+
+        let node = result.child("skewed");
+        for _ in 0..scale_count(20, self.scale) {
+            let size = rng.gen_range(1000..20_000);
+            let data = (0..size).map(|_| if rng.gen_bool(0.9) { b'x' } else { rng.gen::<u8>() }).collect();
+            node.add_test(make_test("skewed bytes", data, Some(0.5)));
+        }
+
+        let node = result.child("large");
+        // Every third file is incompressible, so smoke runs keep a single large text
+        for idx in 0..scale_count(3, self.scale) {
+            if idx % 3 == 2 {
+                node.add_test(make_test("large random bytes", (0..(4 << 20)).map(|_| rng.gen::<u8>()).collect(), Some(1.05)));
+            } else {
+                node.add_test(make_test("large text", random_text(rng, 4 << 20), Some(0.7)));
+            }
+        }
+
+        result
+    }
+}
@@ -0,0 +1,65 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Verdict {
+    Ok,
+    WrongAnswer(String),
+    /// The answer is right, but not in the required form
+    PresentationError(String),
+    /// Score from 0 to 1 for answers which are only partly right
+    Partial { score: f64, message: String },
+}
+
+impl Verdict {
+    pub fn is_ok(&self) -> bool {
+        *self == Verdict::Ok
+    }
+
+    /// Score of the test from 0 to 1
+    pub fn score(&self) -> f64 {
+        match self {
+            Verdict::Ok => 1.0,
+            Verdict::Partial { score, .. } => *score,
+            _ => 0.0,
+        }
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Ok => f.write_str("OK"),
+            Verdict::WrongAnswer(message) => write!(f, "Wrong answer: {}", message),
+            Verdict::PresentationError(message) => write!(f, "Presentation error: {}", message),
+            Verdict::Partial { score, message } => write!(f, "Partial score {:.2}: {}", score, message),
+        }
+    }
+}
+
+pub trait Checker<Input, Output> {
+    /// `Err` means the checker itself is broken, which is a run error and not the solution's fault
+    fn check(&self, input: &Input, expected: &Output, actual: &Output) -> Result<Verdict, String>;
+}
+
+/// Default checker, the answer must be equal to the expected one
+pub struct EqualityChecker;
+
+impl<Input, Output: PartialEq> Checker<Input, Output> for EqualityChecker {
+    fn check(&self, _input: &Input, expected: &Output, actual: &Output) -> Result<Verdict, String> {
+        if expected == actual {
+            Ok(Verdict::Ok)
+        } else {
+            Ok(Verdict::WrongAnswer("Answer differs from expected".to_string()))
+        }
+    }
+}
+
+/// Plain check functions reject with a wrong answer
+impl<Input, Output, F> Checker<Input, Output> for F where F: Fn(&Input, &Output, &Output) -> Result<(), String> {
+    fn check(&self, input: &Input, expected: &Output, actual: &Output) -> Result<Verdict, String> {
+        match self(input, expected, actual) {
+            Ok(()) => Ok(Verdict::Ok),
+            Err(message) => Ok(Verdict::WrongAnswer(message)),
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
+
+pub struct Section {
+    pub name: String,
+    entries: Vec<(String, String)>,
+}
+
+impl Section {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn require(&self, key: &str) -> Result<&str, String> {
+        self.get(key).ok_or_else(|| format!("[{}]: missing key '{}'", self.name, key))
+    }
+
+    pub fn parse<T: FromStr>(&self, key: &str) -> Result<T, String> where T::Err: ToString {
+        let value = self.require(key)?;
+        T::from_str(value).map_err(|a| format!("[{}] {}: cannot parse '{}': {}", self.name, key, value, a.to_string()))
+    }
+
+    /// Like `parse`, but a missing key gives `default`
+    pub fn parse_or<T: FromStr>(&self, key: &str, default: T) -> Result<T, String> where T::Err: ToString {
+        match self.get(key) {
+            Some(_) => self.parse(key),
+            None => Ok(default),
+        }
+    }
+
+    pub fn parse_range(&self, key: &str) -> Result<Range<i32>, String> {
+        let value = self.require(key)?;
+        let (start, end) = value.split_once("..")
+            .ok_or_else(|| format!("[{}] {}: expected range like 1..10 but got '{}'", self.name, key, value))?;
+        let start = i32::from_str(start.trim()).map_err(|a| format!("[{}] {}: {}", self.name, key, a))?;
+        let end = i32::from_str(end.trim()).map_err(|a| format!("[{}] {}: {}", self.name, key, a))?;
+        if start >= end {
+            return Err(format!("[{}] {}: range {} is empty", self.name, key, value));
+        }
+        Ok(start..end)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item=&str> {
+        self.entries.iter().map(|(k, _)| k.as_str())
+    }
+}
+
+pub struct Config {
+    pub sections: Vec<Section>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Config, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|a| format!("Cannot read config {}: {}", path.display(), a))?;
+        Config::parse(&text).map_err(|a| format!("{}: {}", path.display(), a))
+    }
+
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let mut sections: Vec<Section> = Vec::new();
+
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[') {
+                let name = name.strip_suffix(']')
+                    .ok_or_else(|| format!("line {}: unterminated section header", idx + 1))?;
+                sections.push(Section { name: name.trim().to_string(), entries: Vec::new() });
+                continue;
+            }
+
+            let (key, value) = line.split_once('=')
+                .ok_or_else(|| format!("line {}: expected 'key = value'", idx + 1))?;
+            let section = sections.last_mut()
+                .ok_or_else(|| format!("line {}: key outside of any section", idx + 1))?;
+            section.entries.push((key.trim().to_string(), value.trim().to_string()));
+        }
+
+        Ok(Config { sections })
+    }
+}
@@ -0,0 +1,117 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::base::workdir::WorkDir;
+
+/// How many never executed lines are listed in the report
+const REPORT_LINES: usize = 100;
+
+struct Line {
+    text: String,
+    executed: bool,
+}
+
+/// Student's C sources built with `gcc --coverage`. Every run of the executable adds to the counters,
+/// `collect` reads them with `gcov` and remembers which lines have ever been executed
+pub struct Coverage {
+    dir: WorkDir,
+    executable: PathBuf,
+    lines: RefCell<BTreeMap<(String, u32), Line>>,
+}
+
+impl Coverage {
+    pub fn build(sources: &[PathBuf]) -> Result<Coverage, String> {
+        let dir = WorkDir::new()?;
+        let executable = dir.file("lab");
+        let output = Command::new("gcc")
+            .args(["--coverage", "-O0", "-o"])
+            .arg(&executable)
+            .args(sources)
+            .arg("-lm")
+            .output()
+            .map_err(|a| format!("Cannot start gcc: {}", a))?;
+        if !output.status.success() {
+            return Err(format!("Cannot build instrumented executable:\n{}", String::from_utf8_lossy(&output.stderr)));
+        }
+        Ok(Coverage { dir, executable, lines: RefCell::new(BTreeMap::new()) })
+    }
+
+    pub fn executable(&self) -> &Path {
+        &self.executable
+    }
+
+    /// Reads the counters and returns how many lines were executed for the first time
+    pub fn collect(&self) -> Result<usize, String> {
+        let notes = std::fs::read_dir(self.dir.path())
+            .map_err(|a| format!("Cannot read {}: {}", self.dir.path().display(), a))?
+            .filter_map(|a| a.ok().map(|a| a.path()))
+            .filter(|a| a.extension().is_some_and(|a| a == "gcno"))
+            .collect::<Vec<PathBuf>>();
+        let output = Command::new("gcov")
+            .arg("--stdout")
+            .args(&notes)
+            .current_dir(self.dir.path())
+            .output()
+            .map_err(|a| format!("Cannot start gcov: {}", a))?;
+        if !output.status.success() {
+            return Err(format!("gcov failed:\n{}", String::from_utf8_lossy(&output.stderr)));
+        }
+
+        let mut lines = self.lines.borrow_mut();
+        let mut gain = 0;
+        let mut source = String::new();
+        // Lines look like `<count>:<line>:<text>`, count is `-` for lines without code and `#####` if never executed
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let mut parts = line.splitn(3, ':');
+            let (count, number, text) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(count), Some(number), Some(text)) => (count.trim(), number.trim(), text),
+                _ => continue,
+            };
+            if number == "0" {
+                if let Some(name) = text.strip_prefix("Source:") {
+                    source = name.to_string();
+                }
+                continue;
+            }
+            let number: u32 = match number.parse() {
+                Ok(number) => number,
+                Err(_) => continue,
+            };
+            if count == "-" {
+                continue;
+            }
+            let executed = !count.starts_with("#####") && !count.starts_with("=====");
+            let entry = lines.entry((source.clone(), number))
+                .or_insert_with(|| Line { text: text.trim().to_string(), executed: false });
+            if executed && !entry.executed {
+                entry.executed = true;
+                gain += 1;
+            }
+        }
+        Ok(gain)
+    }
+
+    /// Share of executed lines followed by the lines which never ran
+    pub fn report(&self) -> String {
+        let lines = self.lines.borrow();
+        let executed = lines.values().filter(|a| a.executed).count();
+        let mut result = format!("Covered {} of {} lines", executed, lines.len());
+        if !lines.is_empty() {
+            result += &format!(" ({:.1}%)", executed as f64 * 100.0 / lines.len() as f64);
+        }
+
+        let missed = lines.iter().filter(|(_, line)| !line.executed).collect::<Vec<_>>();
+        if !missed.is_empty() {
+            result += "\nNever executed:";
+        }
+        for ((source, number), line) in missed.iter().take(REPORT_LINES) {
+            result += &format!("\n{}:{}: {}", source, number, line.text);
+        }
+        if missed.len() > REPORT_LINES {
+            result += &format!("\n... and {} more", missed.len() - REPORT_LINES);
+        }
+        result
+    }
+}
@@ -0,0 +1,70 @@
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::base::checker::{Checker, Verdict};
+use crate::base::workdir::WorkDir;
+
+/// testlib exit codes
+const EXIT_OK: i32 = 0;
+const EXIT_WRONG_ANSWER: i32 = 1;
+const EXIT_PRESENTATION_ERROR: i32 = 2;
+const EXIT_FAIL: i32 = 3;
+/// `_dirt`: extra data after the answer, which testlib itself reports as a presentation error
+const EXIT_DIRT: i32 = 4;
+const EXIT_POINTS: i32 = 7;
+
+/// Runs a testlib-style checker as `<checker> <input> <actual> <expected>` and reads the verdict from its exit code.
+/// The files hold the `Display` forms of the input and of both answers
+pub struct ExternalChecker {
+    file: PathBuf,
+    dir: WorkDir,
+}
+
+impl ExternalChecker {
+    pub fn new(file: PathBuf) -> Result<Self, String> {
+        Ok(ExternalChecker { file, dir: WorkDir::new()? })
+    }
+
+    fn run(&self, input: &str, expected: &str, actual: &str) -> Result<Verdict, String> {
+        let mut paths = Vec::new();
+        for (name, content) in [("input", input), ("actual", actual), ("expected", expected)] {
+            let path = self.dir.file(name);
+            std::fs::write(&path, content).map_err(|a| format!("Cannot write {}: {}", path.display(), a))?;
+            paths.push(path);
+        }
+
+        let output = Command::new(&self.file)
+            .args(&paths)
+            .output()
+            .map_err(|a| format!("Cannot start checker: {}", a))?;
+        // testlib writes its message to stderr, simpler checkers tend to use stdout
+        let mut message = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if message.is_empty() {
+            message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        }
+
+        match output.status.code() {
+            Some(EXIT_OK) => Ok(Verdict::Ok),
+            Some(EXIT_WRONG_ANSWER) => Ok(Verdict::WrongAnswer(message)),
+            Some(EXIT_PRESENTATION_ERROR) | Some(EXIT_DIRT) => Ok(Verdict::PresentationError(message)),
+            Some(EXIT_POINTS) => {
+                // `quitp` prints `points <score> <message>`
+                let rest = message.strip_prefix("points").unwrap_or(&message).trim_start();
+                let (score, message) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                let score: f64 = score.parse().map_err(|_| format!("Cannot parse checker score '{}'", score))?;
+                Ok(Verdict::Partial { score: score.clamp(0.0, 1.0), message: message.trim().to_string() })
+            }
+            Some(EXIT_FAIL) => Err(format!("Checker failed: {}", message)),
+            Some(code) => Err(format!("Checker exited with unknown code {}: {}", code, message)),
+            None => Err(format!("Checker was killed by a signal: {}", message)),
+        }
+    }
+}
+
+/// A checker which cannot start, crashes or exits with `FAIL` gives a run error instead of blaming the solution
+impl<Input: Display, Output: Display> Checker<Input, Output> for ExternalChecker {
+    fn check(&self, input: &Input, expected: &Output, actual: &Output) -> Result<Verdict, String> {
+        self.run(&input.to_string(), &expected.to_string(), &actual.to_string())
+    }
+}
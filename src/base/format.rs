@@ -0,0 +1,68 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FormatProfile {
+    /// Any whitespace between values, anything `from_str` accepts
+    Lenient,
+    /// Layout required by the lab statement
+    LabSpec,
+    /// Byte-for-byte equality with the reference output
+    ExactBytes,
+}
+
+impl FromStr for FormatProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lenient" => Ok(FormatProfile::Lenient),
+            "lab-spec" => Ok(FormatProfile::LabSpec),
+            "exact-bytes" => Ok(FormatProfile::ExactBytes),
+            _ => Err(format!("Unknown format profile '{}'. Options: lenient, lab-spec, exact-bytes", s))
+        }
+    }
+}
+
+impl Display for FormatProfile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            FormatProfile::Lenient => "lenient",
+            FormatProfile::LabSpec => "lab-spec",
+            FormatProfile::ExactBytes => "exact-bytes",
+        })
+    }
+}
+
+pub fn describe_char(c: char) -> String {
+    match c {
+        '\n' => "'\\n'".to_string(),
+        '\r' => "'\\r'".to_string(),
+        '\t' => "'\\t'".to_string(),
+        c => format!("'{}'", c),
+    }
+}
+
+pub fn compare_bytes(expected: &str, actual: &str) -> Result<(), String> {
+    let expected = expected.as_bytes();
+    let actual = actual.as_bytes();
+
+    let position = expected.iter()
+        .zip(actual.iter())
+        .position(|(a, b)| a != b)
+        .unwrap_or_else(|| expected.len().min(actual.len()));
+
+    if position == expected.len() && position == actual.len() {
+        return Ok(());
+    }
+
+    let context = |bytes: &[u8]| {
+        let end = (position + 10).min(bytes.len());
+        format!("{:?}", String::from_utf8_lossy(&bytes[position.min(bytes.len())..end]))
+    };
+
+    Err(format!(
+        "Output differs from reference at byte {}: expected {}, found {}",
+        position, context(expected), context(actual)
+    ))
+}
@@ -0,0 +1,34 @@
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Accepts plain seconds or a number with one of the suffixes `ms`, `s`, `m`, `h`
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|a: char| !a.is_ascii_digit() && a != '.').unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let value = f64::from_str(value).map_err(|_| format!("Cannot parse duration '{}'", s))?;
+    let seconds = match unit {
+        "ms" => value / 1000.0,
+        "" | "s" => value,
+        "m" => value * 60.0,
+        "h" => value * 3600.0,
+        _ => return Err(format!("Unknown duration unit '{}'. Options: ms, s, m, h", unit)),
+    };
+    if !seconds.is_finite() || seconds <= 0.0 {
+        return Err(format!("Duration must be positive but got {}", s));
+    }
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+/// Writes the failure into `<dir>/crash-<seed>.txt` and returns the path
+pub fn save_crash(dir: &Path, seed: &str, group: &str, failure: &str) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir).map_err(|a| format!("Cannot create {}: {}", dir.display(), a))?;
+    let path = dir.join(format!("crash-{}.txt", seed));
+    let content = format!(
+        "Seed: {}\nGroup: {}\nReplay with --seed {} and the same lab, config and scale\n\n{}\n",
+        seed, group, seed, failure
+    );
+    std::fs::write(&path, content).map_err(|a| format!("Cannot write {}: {}", path.display(), a))?;
+    Ok(path)
+}
@@ -0,0 +1,148 @@
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::{ChildStdin, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+use crate::base::runner::{Listener, Runner};
+use crate::base::tests_supply::Test;
+
+pub const DEFAULT_ROUND_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How many of the last lines are shown when a dialogue fails
+const TRANSCRIPT_LINES: usize = 20;
+
+/// Verdict of one interactive test, decided by the `Interactor`
+#[derive(Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Accepted,
+    Rejected(String),
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Accepted => f.write_str("Accepted"),
+            Outcome::Rejected(message) => f.write_str(message),
+        }
+    }
+}
+
+/// Line based conversation with the child process
+pub struct Dialogue<'a> {
+    stdin: &'a mut ChildStdin,
+    lines: &'a Receiver<std::io::Result<String>>,
+    timeout: Duration,
+    transcript: VecDeque<String>,
+}
+
+impl<'a> Dialogue<'a> {
+    fn record(&mut self, line: String) {
+        if self.transcript.len() == TRANSCRIPT_LINES {
+            self.transcript.pop_front();
+        }
+        self.transcript.push_back(line);
+    }
+
+    pub fn send(&mut self, line: &str) -> Result<(), String> {
+        self.record(format!("> {}", line));
+        writeln!(self.stdin, "{}", line)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|a| format!("Cannot write to process: {}", a))
+    }
+
+    /// Next line without the line terminator, fails if nothing comes within the round timeout
+    pub fn receive(&mut self) -> Result<String, String> {
+        match self.lines.recv_timeout(self.timeout) {
+            Ok(Ok(line)) => {
+                let line = line.trim_end_matches(['\r', '\n']).to_string();
+                self.record(format!("< {}", line));
+                Ok(line)
+            }
+            Ok(Err(e)) => Err(format!("Cannot read from process output: {}", e)),
+            Err(RecvTimeoutError::Timeout) => Err(format!("No answer within {} ms", self.timeout.as_millis())),
+            Err(RecvTimeoutError::Disconnected) => Err("Process closed its output".to_string()),
+        }
+    }
+
+    fn transcript(&self) -> String {
+        self.transcript.iter().cloned().collect::<Vec<String>>().join("\n")
+    }
+}
+
+/// Plays one test with the child. Returning `Err` rejects the solution with that message
+pub trait Interactor<Input> {
+    fn interact(&self, input: &Input, dialogue: &mut Dialogue) -> Result<(), String>;
+}
+
+/// Starts a fresh process for every test, since a failed dialogue leaves it in an unknown state
+pub struct InteractiveRunner<Input> {
+    file: PathBuf,
+    interactor: Box<dyn Interactor<Input>>,
+    timeout: Duration,
+}
+
+impl<Input> InteractiveRunner<Input> {
+    pub fn new<I: 'static + Interactor<Input>>(file: PathBuf, interactor: I) -> Self {
+        InteractiveRunner { file, interactor: Box::new(interactor), timeout: DEFAULT_ROUND_TIMEOUT }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn play(&self, input: &Input) -> Result<Outcome, String> {
+        let mut process = std::process::Command::new(&self.file)
+            .arg("-")
+            .arg("-")
+            .env("TEST", "true")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|a| a.to_string())?;
+
+        let (sender, receiver) = mpsc::channel();
+        let stdout = process.stdout.take().unwrap();
+        // Blocking reads are moved to a thread, so that rounds can time out
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut dialogue = Dialogue {
+            stdin: process.stdin.as_mut().unwrap(),
+            lines: &receiver,
+            timeout: self.timeout,
+            transcript: VecDeque::new(),
+        };
+        let outcome = match self.interactor.interact(input, &mut dialogue) {
+            Ok(()) => Outcome::Accepted,
+            Err(e) => Outcome::Rejected(format!("{}\nLast lines (> sent, < received):\n{}", e, dialogue.transcript())),
+        };
+
+        if process.try_wait().map_err(|a| format!("Cannot check if process is died: {}", a))?.is_none() {
+            process.kill().map_err(|a| format!("Cannot kill process: {}", a))?;
+        }
+        process.wait().map_err(|a| format!("Cannot wait for process: {}", a))?;
+
+        Ok(outcome)
+    }
+}
+
+impl<Input> Runner<Input, Outcome> for InteractiveRunner<Input> {
+    fn run(&self, input: &[Test<Input, Outcome>], listener: &mut Listener<Input, Outcome>) -> Result<(), String> {
+        for test in input {
+            let outcome = self.play(&test.input)?;
+            if !listener(test, &outcome, None) {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,12 @@
+pub mod checker;
+pub mod config;
+pub mod coverage;
+pub mod external_checker;
+pub mod format;
+pub mod fuzz;
+pub mod interactive;
 pub mod runner;
+pub mod scale;
 pub mod tests_supply;
-pub mod test_runner;
\ No newline at end of file
+pub mod workdir;
+pub mod test_runner;
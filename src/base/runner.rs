@@ -3,24 +3,71 @@ use std::io::{Read, Stdin, Write, ErrorKind};
 use std::marker::PhantomData;
 use std::path::PathBuf;
 use std::process::Stdio;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
 
 use crate::base::tests_supply::Test;
+use crate::base::format::FormatProfile;
+
+/// How long a program may take to print the complete answer to one test
+pub const DEFAULT_TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a program may take to exit after its input is closed, when waiting for it is enabled
+pub const DEFAULT_EXIT_GRACE: Duration = Duration::from_millis(100);
 
 pub trait TryFromVerbal: Sized {
     fn try_from(s: &str) -> Result<Self, String>;
+
+    /// Outputs which may legitimately be not UTF-8 override this instead of failing the whole run
+    fn try_from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let s = std::str::from_utf8(bytes).map_err(|a| format!("Cannot parse output to UTF string: {}", a))?;
+        Self::try_from(s)
+    }
+
+    fn check_format(_s: &str, _expected: &Self, _profile: FormatProfile) -> Result<(), String> {
+        Ok(())
+    }
 }
 
+pub trait VerbalInput: ToString {
+    fn is_output_complete(&self, _output: &[u8]) -> bool {
+        true
+    }
+}
+
+pub type Listener<'a, Input, Output> = dyn FnMut(&Test<Input, Output>, &Output, Option<String>) -> bool + 'a;
+
 pub trait Runner<Input, Output> {
-    fn run(&self, input: &[Test<Input, Output>], listener: &mut dyn FnMut(&Test<Input, Output>, &Output) -> bool) -> Result<(), String>;
+    fn run(&self, input: &[Test<Input, Output>], listener: &mut Listener<Input, Output>) -> Result<(), String>;
 }
 
 pub struct BatchStdIORunner {
     file: PathBuf,
+    profile: FormatProfile,
+    exit_grace: Option<Duration>,
+    timeout: Duration,
 }
 
 impl BatchStdIORunner {
     pub fn new(file: PathBuf) -> Self {
-        BatchStdIORunner { file }
+        BatchStdIORunner { file, profile: FormatProfile::Lenient, exit_grace: None, timeout: DEFAULT_TEST_TIMEOUT }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Closes the input after the tests and lets the program exit on its own before it is killed,
+    /// so that it can write what it keeps until exit, like coverage counters
+    pub fn with_exit_grace(mut self, grace: Duration) -> Self {
+        self.exit_grace = Some(grace);
+        self
+    }
+
+    pub fn with_format(mut self, profile: FormatProfile) -> Self {
+        self.profile = profile;
+        self
     }
 }
 
@@ -39,8 +86,8 @@ fn enforce_interruptable_io<T, F>(block: &mut F) -> std::io::Result<T> where F :
     }
 }
 
-impl<Input: ToString, Output: TryFromVerbal> Runner<Input, Output> for BatchStdIORunner {
-    fn run(&self, input: &[Test<Input, Output>], listener: &mut dyn FnMut(&Test<Input, Output>, &Output) -> bool) -> Result<(), String> {
+impl<Input: VerbalInput, Output: TryFromVerbal> Runner<Input, Output> for BatchStdIORunner {
+    fn run(&self, input: &[Test<Input, Output>], listener: &mut Listener<Input, Output>) -> Result<(), String> {
         let mut cmd = std::process::Command::new(&self.file);
         cmd.arg("-");
         cmd.arg("-");
@@ -50,33 +97,89 @@ impl<Input: ToString, Output: TryFromVerbal> Runner<Input, Output> for BatchStdI
 
         let mut process = cmd.spawn().map_err(|a| a.to_string())?;
 
-        let process_input = process.stdin.as_mut().unwrap();
-        let process_output = process.stdout.as_mut().unwrap();
+        let mut process_input = process.stdin.take().unwrap();
+        let mut process_output = process.stdout.take().unwrap();
+
+        let (input_sender, inputs) = mpsc::channel::<String>();
+        // Writes are moved to a thread too: a program which prints while reading a large input
+        // would otherwise fill its output pipe and wait for us while we wait for it to read.
+        // A program which stops reading shows up on the output side, so write errors just end the thread
+        std::thread::spawn(move || {
+            for s in inputs {
+                let written = enforce_interruptable_io(&mut || process_input.write_all(s.as_bytes()))
+                    .and_then(|_| process_input.flush());
+                if written.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let (sender, receiver) = mpsc::channel();
+        // Blocking reads are moved to a thread, so that a test without a complete answer times out
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 65536];
+            loop {
+                match enforce_interruptable_io(&mut || process_output.read(&mut buf[..])) {
+                    Ok(0) => break,
+                    Ok(read) => {
+                        if sender.send(Ok(buf[..read].to_vec())).is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        let _ = sender.send(Err(e));
+                        break;
+                    }
+                }
+            }
+        });
 
 
         for test in input {
-            let s = test.input.to_string();
-
-            enforce_interruptable_io(&mut || {
-                process_input.write_all(s.as_bytes())
-            }).map_err(|a| format!("Cannot write to process: {}", a))?;
-            process_input.flush().map_err(|a| format!("Cannot flush to process output: {}", a))?;
+            input_sender.send(test.input.to_string())
+                .map_err(|_| "Cannot write to process: it stopped reading its input".to_string())?;
 
             let mut output = Vec::<u8>::new();
-            let mut buf = [0u8; 1024];
-
-            let read = enforce_interruptable_io(&mut || {
-                process_output.read(&mut buf[..])
-            }).map_err(|a| format!("Cannot read from process output: {}", a))?;
+            let deadline = Instant::now() + self.timeout;
+
+            loop {
+                match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(Ok(read)) => {
+                        output.extend_from_slice(&read);
+                        // Everything already read is taken at once, since the check scans the whole output
+                        // and programs writing in small pieces would make it quadratic
+                        loop {
+                            match receiver.try_recv() {
+                                Ok(Ok(read)) => output.extend_from_slice(&read),
+                                Ok(Err(e)) => return Err(format!("Cannot read from process output: {}", e)),
+                                Err(_) => break,
+                            }
+                        }
+                        if test.input.is_output_complete(&output) {
+                            break;
+                        }
+                    }
+                    Ok(Err(e)) => return Err(format!("Cannot read from process output: {}", e)),
+                    // The output is closed, whatever was printed is checked
+                    Err(RecvTimeoutError::Disconnected) => break,
+                    Err(RecvTimeoutError::Timeout) => {
+                        process.kill().map_err(|a| format!("Cannot kill process: {}", a))?;
+                        return Err(format!(
+                            "Time limit exceeded: no complete answer within {} ms, got {} bytes",
+                            self.timeout.as_millis(), output.len()
+                        ));
+                    }
+                }
+            }
 
-            &buf[0..read].iter().for_each(|a| output.push(*a));
 
+            let text = String::from_utf8_lossy(&output);
 
-            let output = String::from_utf8(output).map_err(|a| format!("Cannot parse output to UTF string: {}", a))?;
+            let format_error = Output::check_format(&text, &test.output, self.profile).err();
 
-            let output = Output::try_from(&output).map_err(|a| format!("{}: {}", a, output))?;
+            let output = Output::try_from_bytes(&output).map_err(|a| format!("{}: {}", a, text))?;
 
-            if !listener(&test, &output) {
+            if !listener(&test, &output, format_error) {
                 break;
             }
         }
@@ -88,8 +191,22 @@ impl<Input: ToString, Output: TryFromVerbal> Runner<Input, Output> for BatchStdI
                     return Err(format!("Process exited with exit code: {}", code.code().unwrap()));
                 }
             }
-            None => {
-                process.kill().map_err(|a| format!("Cannot kill process: {}", a))?;
+            None => match self.exit_grace {
+                // Exit code after the closed input is not checked, since the tests are over
+                Some(grace) => {
+                    drop(input_sender);
+                    let deadline = Instant::now() + grace;
+                    while process.try_wait().map_err(|a| format!("Cannot check if process is died: {}", a))?.is_none() {
+                        if Instant::now() >= deadline {
+                            process.kill().map_err(|a| format!("Cannot kill process: {}", a))?;
+                            break;
+                        }
+                        std::thread::sleep(Duration::from_millis(2));
+                    }
+                }
+                None => {
+                    process.kill().map_err(|a| format!("Cannot kill process: {}", a))?;
+                }
             }
         }
 
@@ -0,0 +1,58 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunProfile {
+    Smoke,
+    Normal,
+    Exhaustive,
+}
+
+impl RunProfile {
+    pub fn factor(&self) -> f64 {
+        match self {
+            RunProfile::Smoke => 0.05,
+            RunProfile::Normal => 1.0,
+            RunProfile::Exhaustive => 5.0,
+        }
+    }
+}
+
+impl FromStr for RunProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "smoke" => Ok(RunProfile::Smoke),
+            "normal" => Ok(RunProfile::Normal),
+            "exhaustive" => Ok(RunProfile::Exhaustive),
+            _ => Err(format!("Unknown profile '{}'. Options: smoke, normal, exhaustive", s))
+        }
+    }
+}
+
+impl Display for RunProfile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            RunProfile::Smoke => "smoke",
+            RunProfile::Normal => "normal",
+            RunProfile::Exhaustive => "exhaustive",
+        })
+    }
+}
+
+pub fn parse_factor(s: &str) -> Result<f64, String> {
+    let factor = f64::from_str(s).map_err(|a| a.to_string())?;
+    if !factor.is_finite() || factor <= 0.0 {
+        return Err(format!("Scale must be a positive number but got {}", s));
+    }
+    Ok(factor)
+}
+
+/// Non-empty groups never scale down to nothing
+pub fn scale_count(count: usize, factor: f64) -> usize {
+    if count == 0 {
+        return 0;
+    }
+    ((count as f64 * factor).round() as usize).max(1)
+}
@@ -1,17 +1,52 @@
-use crate::base::tests_supply::{TestsSupplier, TestsNode, Test};
+use crate::base::tests_supply::{self, TestsSupplier, TestsNode, Test, Scoring};
+use crate::base::fuzz;
 use crate::base::runner::Runner;
+use crate::base::checker::{Checker, EqualityChecker, Verdict};
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter, Debug};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use crate::base::coverage::Coverage;
 use rand::RngCore;
+use rand::distributions::{Distribution, WeightedIndex};
 
 
-pub struct TestsRunner<Input, Output> where Input: Clone+Display, Output: Eq, Output: Clone+Display {
+pub struct TestsRunner<Input, Output> where Input: Clone+Display, Output: Clone+Display {
     supplier: Box<dyn TestsSupplier<Input, Output>>,
-    runner: Box<dyn Runner<Input, Output>>
+    runner: Box<dyn Runner<Input, Output>>,
+    checker: Box<dyn Checker<Input, Output>>,
+    grading: bool,
+    coverage: Option<Coverage>,
+    chunk_note: Option<Box<ChunkNote<Input, Output>>>
+}
+
+/// Note about one answered test with its rank, the highest ranked note of a chunk is shown on its line
+pub type ChunkNote<Input, Output> = dyn Fn(&Test<Input, Output>, &Output) -> Option<(f64, String)>;
+
+pub struct RunReport<Input, Output> {
+    pub failed: Option<FailedTest<Input, Output>>,
+    /// Weighted share of the passed tests from 0 to 1, only when grading
+    pub score: Option<f64>,
+    /// Groups not run because a group they require did not pass
+    pub skipped: Vec<String>
+}
+
+/// Which groups passed so far by their paths, to decide whether the groups requiring them run
+#[derive(Default)]
+struct RunState {
+    passed: HashMap<String, bool>,
+    skipped: Vec<String>
+}
+
+struct NodeResult<Input, Output> {
+    score: f64,
+    failed: Option<FailedTest<Input, Output>>
 }
 
 pub struct FailedTest<Input, Output> {
     pub test: Test<Input, Output>,
-    pub answer: Output
+    pub answer: Output,
+    pub verdict: Verdict
 }
 
 impl <Input: Display, Output: Display>Debug for FailedTest<Input, Output> {
@@ -22,11 +57,13 @@ impl <Input: Display, Output: Display>Debug for FailedTest<Input, Output> {
 
 impl <Input: Display, Output: Display>Display for FailedTest<Input, Output> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!("Input:\n{}\nExpected:\n{}\nActual:\n{}", self.test.input, self.test.output, self.answer))
+        f.write_fmt(format_args!("Input:\n{}\nExpected:\n{}\nActual:\n{}", self.test.input, self.test.output, self.answer))?;
+        f.write_fmt(format_args!("\nReason:\n{}", self.verdict))
     }
 }
 
-impl <Input, Output>TestsRunner<Input, Output> where Input: Clone+Display, Output: Eq, Output: Clone+Display {
+impl <Input, Output>TestsRunner<Input, Output> where Input: Clone+Display, Output: PartialEq, Output: Clone+Display {
+    /// Answers are compared for equality until another checker is set
     pub fn new<Supplier, Run>(supplier: Supplier, runner: Run) -> TestsRunner<Input, Output>
         where Supplier: 'static + TestsSupplier<Input, Output>,
               Run: 'static + Runner<Input, Output>
@@ -34,71 +71,327 @@ impl <Input, Output>TestsRunner<Input, Output> where Input: Clone+Display, Outpu
         TestsRunner {
             supplier: Box::new(supplier),
             runner: Box::new(runner),
+            checker: Box::new(EqualityChecker),
+            grading: false,
+            coverage: None,
+            chunk_note: None,
         }
     }
+}
 
-    pub fn run(&self, rng: &mut dyn RngCore) -> Result<Option<FailedTest<Input, Output>>, String> {
+impl <Input, Output>TestsRunner<Input, Output> where Input: Clone+Display, Output: Clone+Display {
+    pub fn with_checker<C>(mut self, checker: C) -> Self
+        where C: 'static + Checker<Input, Output>
+    {
+        self.checker = Box::new(checker);
+        self
+    }
+
+    /// Format problems reported by the runner turn an accepted answer into a presentation error
+    fn verdict(&self, test: &Test<Input, Output>, output: &Output, format_error: Option<String>) -> Result<Verdict, String> {
+        let verdict = self.checker.check(&test.input, &test.output, output)?;
+        Ok(match (verdict, format_error) {
+            (verdict, None) => verdict,
+            (Verdict::Ok, Some(format_error)) => Verdict::PresentationError(format_error),
+            (Verdict::WrongAnswer(message), Some(format_error)) =>
+                Verdict::WrongAnswer(format!("{}\nPresentation error: {}", message, format_error)),
+            (Verdict::PresentationError(message), Some(format_error)) =>
+                Verdict::PresentationError(format!("{}\n{}", message, format_error)),
+            (Verdict::Partial { score, message }, Some(format_error)) =>
+                Verdict::Partial { score, message: format!("{}\nPresentation error: {}", message, format_error) },
+        })
+    }
+
+    /// Runs every test instead of stopping at the first failure and reports the total score
+    pub fn with_grading(mut self, grading: bool) -> Self {
+        self.grading = grading;
+        self
+    }
+
+    /// Reads line coverage after every chunk and reports the lines which never ran
+    pub fn with_coverage(mut self, coverage: Coverage) -> Self {
+        self.coverage = Some(coverage);
+        self
+    }
+
+    /// Shows something about passed tests too, like the worst compression ratio of a chunk
+    pub fn with_chunk_note<F>(mut self, note: F) -> Self
+        where F: 'static + Fn(&Test<Input, Output>, &Output) -> Option<(f64, String)>
+    {
+        self.chunk_note = Some(Box::new(note));
+        self
+    }
+
+    pub fn run(&self, rng: &mut dyn RngCore) -> Result<RunReport<Input, Output>, String> {
         println!("Generating tests...");
         let tests = self.supplier.supply_tests(rng);
         println!("Tests generated");
-        self.run_node(0, &tests)
+        Self::check_requirements(&tests, "", &mut HashMap::new())?;
+        let mut state = RunState::default();
+        let result = self.run_node(0, &tests, "", &mut state)?;
+        self.print_coverage();
+        Ok(RunReport {
+            failed: result.failed,
+            score: if self.grading { Some(result.score) } else { None },
+            skipped: state.skipped,
+        })
+    }
+
+    /// Required groups must be run before the groups requiring them, and their paths must be unique
+    fn check_requirements(node: &TestsNode<Input, Output>, path: &str, seen: &mut HashMap<String, usize>) -> Result<(), String> {
+        for name in &node.requires {
+            match seen.get(name) {
+                None => return Err(format!("Group '{}' requires '{}', which is not run before it", path, name)),
+                Some(1) => {}
+                Some(_) => return Err(format!("Group '{}' requires '{}', but several groups have this path", path, name)),
+            }
+        }
+        for child in &node.children {
+            Self::check_requirements(child, &child_path(path, &child.name), seen)?;
+        }
+        *seen.entry(path.to_string()).or_insert(0) += 1;
+        Ok(())
+    }
+
+    /// Runs the generated groups with a fresh seed every round until the time is over or a test fails.
+    /// The failure is saved to `crash_dir` and returned along with the path.
+    /// With coverage, chunks are drawn more often from the groups which recently reached new lines
+    pub fn fuzz(&self, duration: Duration, crash_dir: &Path, rng: &mut dyn RngCore) -> Result<Option<String>, String> {
+        let start = Instant::now();
+        let mut count = 0;
+        let mut round = 0;
+        let mut energy: HashMap<String, f64> = HashMap::new();
+        while start.elapsed() < duration {
+            round += 1;
+            let seed = format!("{:016x}", rng.next_u64());
+            let tests = self.supplier.supply_tests(&mut tests_supply::seeded_rng(&seed));
+            let mut nodes = Vec::new();
+            Self::fuzzed_nodes(&tests, "", &mut nodes);
+            if nodes.is_empty() {
+                Self::all_nodes(&tests, "", &mut nodes);
+            }
+
+            // Groups are told apart by full path, since nested groups of different parents may share a name
+            let mut pending = nodes.into_iter().map(|(path, node)| (path, node.iter_tests())).collect::<Vec<_>>();
+            while !pending.is_empty() && start.elapsed() < duration {
+                let idx = match &self.coverage {
+                    Some(_) => {
+                        let weights = pending.iter().map(|(path, _)| *energy.get(path).unwrap_or(&1.0));
+                        WeightedIndex::new(weights).map_err(|a| a.to_string())?.sample(rng)
+                    }
+                    None => 0,
+                };
+                let (path, tests) = &mut pending[idx];
+                let chunk: Vec<Test<Input, Output>> = tests.by_ref().take(10).collect();
+                if chunk.is_empty() {
+                    let _ = pending.remove(idx);
+                    continue;
+                }
+
+                let failure = match self.run_chunk(&chunk, &mut Vec::new(), &mut None) {
+                    Ok(None) => None,
+                    Ok(Some(failed)) => Some(failed.to_string()),
+                    Err(e) => Some(e),
+                };
+                count += chunk.len();
+                if let Some(failure) = failure {
+                    let saved = fuzz::save_crash(crash_dir, &seed, path, &failure)?;
+                    println!("Round {}: failed in '{}' with seed {}", round, path, seed);
+                    self.print_coverage();
+                    return Ok(Some(format!("{}\nSaved to {}", failure, saved.display())))
+                }
+
+                if let Some(coverage) = &self.coverage {
+                    let gain = coverage.collect()?;
+                    let energy = energy.entry(path.clone()).or_insert(1.0);
+                    *energy = if gain > 0 { *energy + gain as f64 } else { (*energy * 0.8).max(0.05) };
+                }
+            }
+
+            let elapsed = start.elapsed().as_secs_f64();
+            println!("Round {}: {} tests in {:.0}s, {:.1} tests/s", round, count, elapsed, count as f64 / elapsed);
+        }
+        println!("No failures in {} tests", count);
+        self.print_coverage();
+        Ok(None)
+    }
+
+    fn print_coverage(&self) {
+        if let Some(coverage) = &self.coverage {
+            println!("{}", coverage.report());
+        }
+    }
+
+    fn fuzzed_nodes<'a>(node: &'a TestsNode<Input, Output>, path: &str, result: &mut Vec<(String, &'a TestsNode<Input, Output>)>) {
+        if node.generator.is_some() {
+            result.push((path.to_string(), node));
+        }
+        for child in &node.children {
+            Self::fuzzed_nodes(child, &child_path(path, &child.name), result);
+        }
     }
 
-    fn run_chunk(&self, chunk: &[Test<Input, Output>]) -> Result<Option<FailedTest<Input, Output>>, String> {
+    /// Labs without generated groups are fuzzed with every test
+    fn all_nodes<'a>(node: &'a TestsNode<Input, Output>, path: &str, result: &mut Vec<(String, &'a TestsNode<Input, Output>)>) {
+        if !node.is_empty() {
+            result.push((path.to_string(), node));
+        }
+        for child in &node.children {
+            Self::all_nodes(child, &child_path(path, &child.name), result);
+        }
+    }
+
+    fn run_chunk(&self, chunk: &[Test<Input, Output>], scores: &mut Vec<f64>,
+                 note: &mut Option<(f64, String)>) -> Result<Option<FailedTest<Input, Output>>, String> {
         let mut result: Option<FailedTest<Input, Output>> = None;
-        let mut last_test: Option<Test<Input, Output>> = None;
-        let run_result = self.runner.run(chunk, &mut |test, output| {
-            if !test.output.eq(output) {
+        let mut answered = 0;
+        let mut checker_error = None;
+        let run_result = self.runner.run(chunk, &mut |test, output, format_error| {
+            answered += 1;
+            if let Some((rank, text)) = self.chunk_note.as_ref().and_then(|a| a(test, output)) {
+                if note.as_ref().is_none_or(|(best, _)| rank > *best) {
+                    *note = Some((rank, text));
+                }
+            }
+            let verdict = match self.verdict(test, output, format_error) {
+                Ok(verdict) => verdict,
+                Err(e) => {
+                    checker_error = Some(e);
+                    return false
+                }
+            };
+            scores.push(verdict.score());
+            if !verdict.is_ok() {
                 result = Some(FailedTest {
                     test: test.clone(),
                     answer: output.clone(),
+                    verdict,
                 })
             }
-            last_test = Some(test.clone());
             true
         });
 
-        if let Err(e) = run_result {
-            let last_test = last_test.or(chunk.first().cloned()).map(|a| a.to_string()).unwrap();
-            return Err(format!("{}\nLast run test:\n{}", e, last_test))
+        // Checker errors belong to the last answered test, runner errors to the test after it
+        let error = match (run_result, checker_error) {
+            (_, Some(e)) => Some((e, answered - 1)),
+            (Err(e), None) => Some((e, answered.min(chunk.len() - 1))),
+            (Ok(()), None) => None,
+        };
+        if let Some((e, idx)) = error {
+            return Err(format!("{}\nLast run test:\n{}", e, chunk[idx]))
         }
 
         Ok(result)
     }
 
-    fn run_node(&self, indent_size: u16, node: &TestsNode<Input, Output>) -> Result<Option<FailedTest<Input, Output>>, String>{
+    /// Without grading the first failure ends the run, otherwise it is remembered and crashed chunks score zero
+    fn run_tests(&self, indent: &str, node: &TestsNode<Input, Output>, chunk: &[Test<Input, Output>],
+                 scores: &mut Vec<f64>, failed: &mut Option<FailedTest<Input, Output>>) -> Result<bool, String> {
+        let mut note = None;
+        let result = self.run_chunk(chunk, scores, &mut note);
+        let mut gain = note.map(|(_, text)| format!(", {}", text)).unwrap_or_default();
+        if let Some(coverage) = &self.coverage {
+            gain.push_str(&format!(", +{} lines", coverage.collect()?));
+        }
+        match result {
+            Ok(None) => println!(" Passed{}", gain),
+            Ok(Some(e)) => {
+                if !self.grading {
+                    *failed = Some(e);
+                    return Ok(false)
+                }
+                println!(" Failed{}", gain);
+                failed.get_or_insert(e);
+            }
+            Err(e) => {
+                if !self.grading {
+                    return Err(e)
+                }
+                println!(" Crashed{}", gain);
+                println!("{}\t{} {}", indent, node.name, e.lines().next().unwrap_or_default());
+            }
+        }
+        Ok(true)
+    }
+
+    fn run_node(&self, indent_size: u16, node: &TestsNode<Input, Output>, path: &str, state: &mut RunState) -> Result<NodeResult<Input, Output>, String>{
         let indent = String::from("\t").repeat(indent_size.clone() as usize);
-        if node.tests.is_empty() {
+        if let Some(name) = node.requires.iter().find(|a| !state.passed.get(a.as_str()).copied().unwrap_or(false)) {
+            println!("{}{}: Skipped, requires '{}'", indent, node.name, name);
+            state.passed.insert(path.to_string(), false);
+            state.skipped.push(path.to_string());
+            return Ok(NodeResult { score: 0.0, failed: None })
+        }
+        let count = node.len();
+        if node.is_empty() {
             println!("{}{}", indent, node.name);
-        } else if node.tests.len() > 1 {
-            print!("{}{}: {} ", indent, node.name, node.tests.len());
+        } else if count > 1 {
+            print!("{}{}: {} ", indent, node.name, count);
         } else {
             print!("{}{}:", indent, node.name);
         }
 
-        if node.tests.len() > 10 {
+        let mut failed = None;
+        let mut scores = Vec::new();
+        // Tests are taken from the node chunk by chunk, so generated ones are made right before they run
+        let mut tests = node.iter_tests();
+        if count > 10 {
             println!();
             let mut cnt = 0;
-            for chunk in node.tests.chunks(10) {
+            loop {
+                let chunk: Vec<Test<Input, Output>> = tests.by_ref().take(10).collect();
+                if chunk.is_empty() {
+                    break;
+                }
                 cnt += chunk.len();
                 print!("{}\t{} {}:", indent, node.name, cnt);
-                if let Some(e) = self.run_chunk(chunk)? {
-                    return Ok(Some(e))
+                if !self.run_tests(&indent, node, &chunk, &mut scores, &mut failed)? {
+                    return Ok(NodeResult { score: 0.0, failed })
                 }
-                println!(" Passed");
-            }
-        } else if !node.tests.is_empty() {
-            if let Some(e) = self.run_chunk(&node.tests)? {
-                return Ok(Some(e))
             }
-            println!(" Passed");
+        } else if count > 0 && !self.run_tests(&indent, node, &tests.collect::<Vec<_>>(), &mut scores, &mut failed)? {
+            return Ok(NodeResult { score: 0.0, failed })
+        }
+
+        let mut parts = Vec::new();
+        if count > 0 {
+            // Tests that never got a verdict because of a crash count as failed
+            let tests_score = match node.scoring {
+                Scoring::Proportional => scores.iter().sum::<f64>() / count as f64,
+                Scoring::AllOrNothing => {
+                    let passed = scores.len() == count && scores.iter().all(|a| *a == 1.0);
+                    if passed { 1.0 } else { 0.0 }
+                }
+            };
+            parts.push((1.0, tests_score));
         }
         for child in &node.children {
-            if let Some(e) = self.run_node(indent_size+1, child)? {
-                return Ok(Some(e))
+            let result = self.run_node(indent_size+1, child, &child_path(path, &child.name), state)?;
+            if failed.is_none() {
+                failed = result.failed;
             }
+            if failed.is_some() && !self.grading {
+                return Ok(NodeResult { score: 0.0, failed })
+            }
+            parts.push((child.weight, result.score));
         }
-        return Ok(None)
 
+        let total_weight: f64 = parts.iter().map(|(weight, _)| weight).sum();
+        let score = if total_weight > 0.0 {
+            parts.iter().map(|(weight, score)| weight * score).sum::<f64>() / total_weight
+        } else {
+            1.0
+        };
+        state.passed.insert(path.to_string(), score >= 1.0);
+        Ok(NodeResult { score, failed })
     }
-}
\ No newline at end of file
+}
+
+/// The root has an empty path, so its children are addressed by their bare names
+fn child_path(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", parent, name)
+    }
+}
@@ -1,5 +1,7 @@
 use std::fmt::{Display, Formatter, Debug};
-use rand::RngCore;
+use std::str::FromStr;
+use rand::rngs::SmallRng;
+use rand::{RngCore, SeedableRng};
 
 pub struct Test<Input, Output> {
     pub input: Input,
@@ -36,10 +38,57 @@ impl <A: Display, B: Display> Display for Test<A, B> {
     }
 }
 
+/// How the tests of one node are turned into its score
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scoring {
+    /// Share of the passed tests, partial verdicts count with their score
+    Proportional,
+    /// Full score only if every test passes
+    AllOrNothing,
+}
+
+impl FromStr for Scoring {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "proportional" => Ok(Scoring::Proportional),
+            "all-or-nothing" => Ok(Scoring::AllOrNothing),
+            _ => Err(format!("Unknown scoring '{}'. Options: proportional, all-or-nothing", s))
+        }
+    }
+}
+
+impl Display for Scoring {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Scoring::Proportional => "proportional",
+            Scoring::AllOrNothing => "all-or-nothing",
+        })
+    }
+}
+
+type Generate<Input, Output> = Box<dyn Fn(&mut dyn RngCore) -> Test<Input, Output>>;
+
+/// Makes tests of a node only when they are run, so that large groups never sit in memory at once
+pub struct Generator<Input, Output> {
+    count: usize,
+    /// Drawn from the supplier's rng, so the generated tests still depend only on the run seed
+    seed: u64,
+    generate: Generate<Input, Output>
+}
+
 pub struct TestsNode<Input, Output> {
     pub name: String,
     pub tests: Vec<Test<Input, Output>>,
-    pub children: Vec<TestsNode<Input, Output>>
+    pub generator: Option<Generator<Input, Output>>,
+    pub children: Vec<TestsNode<Input, Output>>,
+    /// Share of the parent's score relative to siblings, own tests of the parent weigh 1
+    pub weight: f64,
+    pub scoring: Scoring,
+    /// Paths of groups run earlier which must pass fully, otherwise this group is skipped.
+    /// A path lists the names from a child of the root down, like `hand-made/1x1`
+    pub requires: Vec<String>
 }
 
 impl <Input, Output>TestsNode<Input, Output> {
@@ -48,16 +97,65 @@ impl <Input, Output>TestsNode<Input, Output> {
         TestsNode {
             name: name.into(),
             tests: Vec::new(),
-            children: Vec::new()
+            generator: None,
+            children: Vec::new(),
+            weight: 1.0,
+            scoring: Scoring::Proportional,
+            requires: Vec::new()
         }
     }
 
+    pub fn set_weight(&mut self, weight: f64) -> &mut Self {
+        self.weight = weight;
+
+        self
+    }
+
+    pub fn set_scoring(&mut self, scoring: Scoring) -> &mut Self {
+        self.scoring = scoring;
+
+        self
+    }
+
+    pub fn require<S: Into<String>>(&mut self, name: S) -> &mut Self {
+        self.requires.push(name.into());
+
+        self
+    }
+
     pub fn add_test(&mut self, test: Test<Input, Output>) -> &mut Self {
         self.tests.push(test);
 
         self
     }
 
+    /// Adds `count` tests made by `generate` when the node runs, after the tests added directly
+    pub fn generate<F>(&mut self, count: usize, rng: &mut dyn RngCore, generate: F) -> &mut Self
+        where F: 'static + Fn(&mut dyn RngCore) -> Test<Input, Output>
+    {
+        self.generator = Some(Generator { count, seed: rng.next_u64(), generate: Box::new(generate) });
+
+        self
+    }
+
+    /// Count of both added and generated tests
+    pub fn len(&self) -> usize {
+        self.tests.len() + self.generator.as_ref().map_or(0, |a| a.count)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Added tests followed by the generated ones, which are made anew on every call
+    pub fn iter_tests(&self) -> impl Iterator<Item=Test<Input, Output>> + '_ where Input: Clone, Output: Clone {
+        let generated = self.generator.iter().flat_map(|generator| {
+            let mut rng = SmallRng::seed_from_u64(generator.seed);
+            (0..generator.count).map(move |_| (generator.generate)(&mut rng))
+        });
+        self.tests.iter().cloned().chain(generated)
+    }
+
     pub fn child<S : Into<String>>(&mut self, name: S) -> &mut TestsNode<Input, Output> {
         let child = TestsNode::new(name);
 
@@ -69,6 +167,17 @@ impl <Input, Output>TestsNode<Input, Output> {
 }
 
 
+/// Rng for the seed given on the command line, a fuzzing run can be replayed by the seed it reports
+pub fn seeded_rng(seed: &str) -> SmallRng {
+    use sha2::Digest;
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(seed);
+    let mut bytes = [77;32];
+    bytes[..].copy_from_slice(&hasher.finalize()[..]);
+
+    SmallRng::from_seed(bytes)
+}
+
 pub trait TestsSupplier<Input, Output> {
     fn supply_tests(&self, rng: &mut dyn RngCore) -> TestsNode<Input, Output>;
 }
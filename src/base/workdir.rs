@@ -0,0 +1,36 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Scratch directory for runners which exchange files with the tested program. Removed on drop
+pub struct WorkDir {
+    path: PathBuf,
+}
+
+impl WorkDir {
+    pub fn new() -> Result<WorkDir, String> {
+        let path = std::env::temp_dir().join(format!(
+            "c-lab-tester-{}-{}", std::process::id(), COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        std::fs::create_dir_all(&path).map_err(|a| format!("Cannot create {}: {}", path.display(), a))?;
+        Ok(WorkDir { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Path to a fresh file, whatever the previous test left there is removed
+    pub fn file(&self, name: &str) -> PathBuf {
+        let path = self.path.join(name);
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+}
+
+impl Drop for WorkDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
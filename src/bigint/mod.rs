@@ -0,0 +1,292 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use rand::{Rng, RngCore};
+
+use crate::base::runner::{TryFromVerbal, VerbalInput};
+use crate::base::scale::scale_count;
+use crate::base::tests_supply::{Test, TestsNode, TestsSupplier};
+use crate::bigint::number::BigInt;
+
+pub mod number;
+
+const OPERATIONS: [Operation; 12] = [
+    Operation::Add, Operation::Sub, Operation::Mul, Operation::Div, Operation::Rem, Operation::Pow,
+    Operation::Less, Operation::Greater, Operation::LessEq, Operation::GreaterEq, Operation::Eq, Operation::NotEq,
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+    Less,
+    Greater,
+    LessEq,
+    GreaterEq,
+    Eq,
+    NotEq,
+}
+
+impl Operation {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Operation::Add => "+",
+            Operation::Sub => "-",
+            Operation::Mul => "*",
+            Operation::Div => "/",
+            Operation::Rem => "%",
+            Operation::Pow => "^",
+            Operation::Less => "<",
+            Operation::Greater => ">",
+            Operation::LessEq => "<=",
+            Operation::GreaterEq => ">=",
+            Operation::Eq => "==",
+            Operation::NotEq => "!=",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Operation::Add => "addition",
+            Operation::Sub => "subtraction",
+            Operation::Mul => "multiplication",
+            Operation::Div => "division",
+            Operation::Rem => "remainder",
+            Operation::Pow => "power",
+            Operation::Less | Operation::Greater | Operation::LessEq |
+            Operation::GreaterEq | Operation::Eq | Operation::NotEq => "comparison",
+        }
+    }
+
+    fn is_comparison(&self) -> bool {
+        self.name() == "comparison"
+    }
+}
+
+#[derive(Clone)]
+pub struct Expression {
+    a: BigInt,
+    operation: Operation,
+    b: BigInt,
+}
+
+impl Expression {
+    fn evaluate(&self) -> BigAnswer {
+        let flag = |a: bool| BigAnswer::Number(BigInt::from(a as i64));
+        match self.operation {
+            Operation::Add => BigAnswer::Number(self.a.add(&self.b)),
+            Operation::Sub => BigAnswer::Number(self.a.sub(&self.b)),
+            Operation::Mul => BigAnswer::Number(self.a.mul(&self.b)),
+            Operation::Div => match self.a.div_rem(&self.b) {
+                Some((quotient, _)) => BigAnswer::Number(quotient),
+                None => BigAnswer::Error(DIVISION_BY_ZERO.to_string()),
+            },
+            Operation::Rem => match self.a.div_rem(&self.b) {
+                Some((_, remainder)) => BigAnswer::Number(remainder),
+                None => BigAnswer::Error(DIVISION_BY_ZERO.to_string()),
+            },
+            Operation::Pow => match self.b.to_u32() {
+                Some(exponent) => BigAnswer::Number(self.a.pow(exponent)),
+                None => BigAnswer::Error(NEGATIVE_EXPONENT.to_string()),
+            },
+            Operation::Less => flag(self.a < self.b),
+            Operation::Greater => flag(self.a > self.b),
+            Operation::LessEq => flag(self.a <= self.b),
+            Operation::GreaterEq => flag(self.a >= self.b),
+            Operation::Eq => flag(self.a == self.b),
+            Operation::NotEq => flag(self.a != self.b),
+        }
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} {} {}", self.a, self.operation.symbol(), self.b)
+    }
+}
+
+impl VerbalInput for Expression {
+    fn is_output_complete(&self, output: &[u8]) -> bool {
+        output.ends_with(b"\n")
+    }
+}
+
+pub const DIVISION_BY_ZERO: &str = "error: division by zero";
+pub const NEGATIVE_EXPONENT: &str = "error: negative exponent";
+
+#[derive(Clone)]
+pub enum BigAnswer {
+    Number(BigInt),
+    Error(String),
+}
+
+/// Any error message is accepted when an error is expected, the wording is up to the student
+impl PartialEq for BigAnswer {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (BigAnswer::Number(a), BigAnswer::Number(b)) => a == b,
+            (BigAnswer::Error(_), BigAnswer::Error(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for BigAnswer {}
+
+impl TryFromVerbal for BigAnswer {
+    fn try_from(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        if s.to_lowercase().starts_with("error") {
+            return Ok(BigAnswer::Error(s.to_string()));
+        }
+        Ok(BigAnswer::Number(BigInt::from_str(s)?))
+    }
+}
+
+impl Display for BigAnswer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BigAnswer::Number(n) => write!(f, "{}", n),
+            BigAnswer::Error(e) => f.write_str(e),
+        }
+    }
+}
+
+pub struct BigIntSupplier {
+    scale: f64,
+}
+
+impl BigIntSupplier {
+    pub fn new(scale: f64) -> BigIntSupplier {
+        BigIntSupplier { scale }
+    }
+}
+
+impl TestsSupplier<Expression, BigAnswer> for BigIntSupplier {
+    fn supply_tests(&self, rng: &mut dyn RngCore) -> TestsNode<Expression, BigAnswer> {
+        let mut result = TestsNode::new("root");
+
+        self.hand_made(result.child("hand-made"));
+
+        let short = result.child("random short");
+        for operation in OPERATIONS.iter() {
+            self.random_group(short, rng, *operation, 1..19, 30);
+        }
+        let long = result.child("random long");
+        for operation in OPERATIONS.iter() {
+            self.random_group(long, rng, *operation, 19..200, 20);
+        }
+        self.carries(result.child("carries"), rng, 50);
+
+        result
+    }
+}
+
+fn make_test(a: BigInt, operation: Operation, b: BigInt) -> Test<Expression, BigAnswer> {
+    let expression = Expression { a, operation, b };
+    let answer = expression.evaluate();
+    Test::new(expression, answer)
+}
+
+fn parse(s: &str) -> BigInt {
+    BigInt::from_str(s).unwrap()
+}
+
+fn random_number(rng: &mut dyn RngCore, digits: std::ops::Range<usize>) -> BigInt {
+    let digits = rng.gen_range(digits);
+    let mut s = String::new();
+    if rng.gen_bool(0.3) {
+        s.push('-');
+    }
+    s.push(rng.gen_range(b'1'..=b'9') as char);
+    for _ in 1..digits {
+        s.push(rng.gen_range(b'0'..=b'9') as char);
+    }
+    parse(&s)
+}
+
+impl BigIntSupplier {
+
+    fn hand_made(&self, node: &mut TestsNode<Expression, BigAnswer>) {
+        node.child("zero")
+            .add_test(make_test(parse("0"), Operation::Add, parse("0")))
+            .add_test(make_test(parse("0"), Operation::Mul, parse("-123456789012345678901234567890")))
+            .add_test(make_test(parse("123456789012345678901234567890"), Operation::Sub, parse("123456789012345678901234567890")))
+            .add_test(make_test(parse("-5"), Operation::Mul, parse("0")))
+            .add_test(make_test(parse("0"), Operation::Div, parse("-7")))
+            .add_test(make_test(parse("0"), Operation::Pow, parse("0")));
+
+        node.child("negatives")
+            .add_test(make_test(parse("-1"), Operation::Add, parse("1")))
+            .add_test(make_test(parse("-1000000000"), Operation::Sub, parse("-999999999")))
+            .add_test(make_test(parse("-7"), Operation::Div, parse("2")))
+            .add_test(make_test(parse("-7"), Operation::Rem, parse("2")))
+            .add_test(make_test(parse("7"), Operation::Rem, parse("-2")))
+            .add_test(make_test(parse("-2"), Operation::Pow, parse("63")))
+            .add_test(make_test(parse("-10"), Operation::Less, parse("-9")))
+            .add_test(make_test(parse("-100000000000000000000"), Operation::GreaterEq, parse("-99999999999999999999")));
+
+        node.child("carries")
+            .add_test(make_test(parse("999999999999999999999999999999"), Operation::Add, parse("1")))
+            .add_test(make_test(parse("1000000000000000000000000000000"), Operation::Sub, parse("1")))
+            .add_test(make_test(parse("999999999999999999"), Operation::Mul, parse("999999999999999999")))
+            .add_test(make_test(parse("18446744073709551616"), Operation::Div, parse("4294967296")))
+            .add_test(make_test(parse("2"), Operation::Pow, parse("200")));
+
+        node.child("equal operands")
+            .add_test(make_test(parse("12345678901234567890"), Operation::Eq, parse("12345678901234567890")))
+            .add_test(make_test(parse("12345678901234567890"), Operation::NotEq, parse("12345678901234567890")))
+            .add_test(make_test(parse("12345678901234567890"), Operation::LessEq, parse("12345678901234567890")))
+            .add_test(make_test(parse("12345678901234567890"), Operation::Div, parse("12345678901234567890")))
+            .add_test(make_test(parse("12345678901234567890"), Operation::Rem, parse("12345678901234567890")));
+
+        node.child("errors")
+            .add_test(make_test(parse("1"), Operation::Div, parse("0")))
+            .add_test(make_test(parse("-123456789012345678901234567890"), Operation::Div, parse("0")))
+            .add_test(make_test(parse("5"), Operation::Rem, parse("0")))
+            .add_test(make_test(parse("2"), Operation::Pow, parse("-1")));
+    }
+
+    fn random_group(&self,
+                    node: &mut TestsNode<Expression, BigAnswer>,
+                    rng: &mut dyn RngCore,
+                    operation: Operation,
+                    digits: std::ops::Range<usize>,
+                    num: usize
+    ) {
+        let name = if operation.is_comparison() {
+            format!("{} {}", operation.name(), operation.symbol())
+        } else {
+            operation.name().to_string()
+        };
+        let node = node.child(name);
+        for _ in 0..scale_count(num, self.scale) {
+            let a = random_number(rng, digits.clone());
+            let b = match operation {
+                // Keeps powers below a few thousand digits
+                Operation::Pow => BigInt::from(rng.gen_range(0..(3000 / digits.end) as i64 + 2)),
+                _ if operation.is_comparison() && rng.gen_bool(0.3) => a.clone(),
+                _ => random_number(rng, digits.clone()),
+            };
+            node.add_test(make_test(a, operation, b));
+        }
+    }
+
+    fn carries(&self, node: &mut TestsNode<Expression, BigAnswer>, rng: &mut dyn RngCore, num: usize) {
+        for _ in 0..scale_count(num, self.scale) {
+            let nines = parse(&"9".repeat(rng.gen_range(9..150)));
+            let power = BigInt::from(10).pow(rng.gen_range(9..150));
+            let (a, operation, b) = match rng.gen_range(0..4) {
+                0 => (nines, Operation::Add, BigInt::from(rng.gen_range(1..1000))),
+                1 => (power, Operation::Sub, BigInt::from(rng.gen_range(1..1000))),
+                2 => (nines.clone(), Operation::Mul, nines),
+                _ => (power.sub(&BigInt::from(1)), Operation::Div, BigInt::from(rng.gen_range(2..1_000_000_000))),
+            };
+            node.add_test(make_test(a, operation, b));
+        }
+    }
+}
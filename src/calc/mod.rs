@@ -0,0 +1,398 @@
+use std::fmt::{Display, Formatter};
+
+use rand::{Rng, RngCore};
+use rand::seq::SliceRandom;
+
+use crate::base::runner::{TryFromVerbal, VerbalInput};
+use crate::base::scale::scale_count;
+use crate::base::tests_supply::{Test, TestsNode, TestsSupplier};
+
+const OPERATORS: [char; 4] = ['+', '-', '*', '/'];
+
+enum Expr {
+    Number(i64),
+    Neg(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+}
+
+fn precedence(operator: char) -> u8 {
+    if operator == '+' || operator == '-' { 1 } else { 2 }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Token {
+    Number(i64),
+    Symbol(char),
+}
+
+fn tokenize(s: &str) -> Option<Vec<Token>> {
+    let mut result = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_whitespace() {
+            continue;
+        }
+        if let Some(digit) = c.to_digit(10) {
+            let mut number = digit as i64;
+            while let Some(digit) = chars.peek().and_then(|a| a.to_digit(10)) {
+                number = number.checked_mul(10)?.checked_add(digit as i64)?;
+                chars.next();
+            }
+            result.push(Token::Number(number));
+        } else if "+-*/()".contains(c) {
+            result.push(Token::Symbol(c));
+        } else {
+            return None;
+        }
+    }
+    Some(result)
+}
+
+/// Recursive descent over `expr := term {+|- term}`, `term := unary {*|/ unary}`, `unary := -unary | number | (expr)`
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.tokens.get(self.position) == Some(&Token::Symbol(symbol)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn binary(&mut self, level: u8) -> Option<Expr> {
+        let mut left = if level == 1 { self.binary(2)? } else { self.unary()? };
+        loop {
+            let operator = OPERATORS.iter().copied().find(|a| precedence(*a) == level && self.eat(*a));
+            match operator {
+                Some(operator) => {
+                    let right = if level == 1 { self.binary(2)? } else { self.unary()? };
+                    left = Expr::Binary(operator, Box::new(left), Box::new(right));
+                }
+                None => return Some(left),
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Option<Expr> {
+        if self.eat('-') {
+            return Some(Expr::Neg(Box::new(self.unary()?)));
+        }
+        if self.eat('(') {
+            let inner = self.binary(1)?;
+            return if self.eat(')') { Some(inner) } else { None };
+        }
+        match self.tokens.get(self.position) {
+            Some(Token::Number(a)) => {
+                self.position += 1;
+                Some(Expr::Number(*a))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn parse(s: &str) -> Option<Expr> {
+    let mut parser = Parser { tokens: tokenize(s)?, position: 0 };
+    let result = parser.binary(1)?;
+    if parser.position == parser.tokens.len() { Some(result) } else { None }
+}
+
+enum EvalError {
+    DivisionByZero,
+    Overflow,
+}
+
+impl Expr {
+    /// C semantics: division truncates toward zero
+    fn evaluate(&self) -> Result<i64, EvalError> {
+        match self {
+            Expr::Number(a) => Ok(*a),
+            Expr::Neg(a) => a.evaluate()?.checked_neg().ok_or(EvalError::Overflow),
+            Expr::Binary(operator, a, b) => {
+                let (a, b) = (a.evaluate()?, b.evaluate()?);
+                let result = match operator {
+                    '+' => a.checked_add(b),
+                    '-' => a.checked_sub(b),
+                    '*' => a.checked_mul(b),
+                    _ if b == 0 => return Err(EvalError::DivisionByZero),
+                    _ => a.checked_div(b),
+                };
+                result.ok_or(EvalError::Overflow)
+            }
+        }
+    }
+
+    /// Only parentheses required by precedence, a negated right operand is parenthesized too
+    fn tokens(&self, result: &mut Vec<String>) {
+        let wrapped = |expr: &Expr, wrap: bool, result: &mut Vec<String>| {
+            if wrap {
+                result.push("(".to_string());
+            }
+            expr.tokens(result);
+            if wrap {
+                result.push(")".to_string());
+            }
+        };
+        match self {
+            Expr::Number(a) => result.push(a.to_string()),
+            Expr::Neg(a) => {
+                result.push("-".to_string());
+                wrapped(a, !matches!(**a, Expr::Number(_)), result);
+            }
+            Expr::Binary(operator, a, b) => {
+                let wrap_left = matches!(**a, Expr::Binary(inner, _, _) if precedence(inner) < precedence(*operator));
+                let wrap_right = match **b {
+                    Expr::Binary(inner, _, _) => precedence(inner) < precedence(*operator)
+                        || (precedence(inner) == precedence(*operator) && (*operator == '-' || *operator == '/')),
+                    Expr::Neg(_) => true,
+                    Expr::Number(_) => false,
+                };
+                wrapped(a, wrap_left, result);
+                result.push(operator.to_string());
+                wrapped(b, wrap_right, result);
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CalcInput {
+    expression: String,
+}
+
+impl Display for CalcInput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.expression)
+    }
+}
+
+impl VerbalInput for CalcInput {
+    fn is_output_complete(&self, output: &[u8]) -> bool {
+        output.ends_with(b"\n")
+    }
+}
+
+pub const SYNTAX_ERROR: &str = "syntax error";
+pub const DIVISION_BY_ZERO: &str = "division by zero";
+
+/// Error messages match by prefix, the rest of the wording is up to the student
+#[derive(Clone)]
+pub enum CalcAnswer {
+    Value(i64),
+    SyntaxError(String),
+    DivisionByZero(String),
+    Unknown(String),
+}
+
+impl PartialEq for CalcAnswer {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (CalcAnswer::Value(a), CalcAnswer::Value(b)) => a == b,
+            (CalcAnswer::SyntaxError(_), CalcAnswer::SyntaxError(_)) => true,
+            (CalcAnswer::DivisionByZero(_), CalcAnswer::DivisionByZero(_)) => true,
+            (CalcAnswer::Unknown(a), CalcAnswer::Unknown(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for CalcAnswer {}
+
+impl TryFromVerbal for CalcAnswer {
+    fn try_from(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        let lower = s.to_lowercase();
+        if lower.starts_with(SYNTAX_ERROR) {
+            return Ok(CalcAnswer::SyntaxError(s.to_string()));
+        }
+        if lower.starts_with(DIVISION_BY_ZERO) {
+            return Ok(CalcAnswer::DivisionByZero(s.to_string()));
+        }
+        Ok(s.parse().map_or_else(|_| CalcAnswer::Unknown(s.to_string()), CalcAnswer::Value))
+    }
+}
+
+impl Display for CalcAnswer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CalcAnswer::Value(a) => write!(f, "{}", a),
+            CalcAnswer::SyntaxError(e) | CalcAnswer::DivisionByZero(e) | CalcAnswer::Unknown(e) => f.write_str(e),
+        }
+    }
+}
+
+/// `None` if the expression overflows `i64`, such tests are not generated
+fn try_make_test(expression: String) -> Option<Test<CalcInput, CalcAnswer>> {
+    let answer = match parse(&expression).map(|a| a.evaluate()) {
+        None => CalcAnswer::SyntaxError(SYNTAX_ERROR.to_string()),
+        Some(Ok(value)) => CalcAnswer::Value(value),
+        Some(Err(EvalError::DivisionByZero)) => CalcAnswer::DivisionByZero(DIVISION_BY_ZERO.to_string()),
+        Some(Err(EvalError::Overflow)) => return None,
+    };
+    Some(Test::new(CalcInput { expression }, answer))
+}
+
+fn make_test(expression: &str) -> Test<CalcInput, CalcAnswer> {
+    try_make_test(expression.to_string()).expect("Overflow in hand-made test")
+}
+
+fn random_expr(rng: &mut dyn RngCore, depth: usize, max_number: i64) -> Expr {
+    if depth == 0 || rng.gen_bool(0.2) {
+        return Expr::Number(rng.gen_range(0..=max_number));
+    }
+    if rng.gen_bool(0.1) {
+        return Expr::Neg(Box::new(random_expr(rng, depth - 1, max_number)));
+    }
+    let operator = *OPERATORS.choose(rng).unwrap();
+    Expr::Binary(
+        operator,
+        Box::new(random_expr(rng, depth - 1, max_number)),
+        Box::new(random_expr(rng, depth - 1, max_number)),
+    )
+}
+
+#[derive(Clone, Copy)]
+enum Spacing {
+    None,
+    Single,
+    Random,
+}
+
+fn join_tokens(rng: &mut dyn RngCore, tokens: &[String], spacing: Spacing) -> String {
+    let mut result = String::new();
+    for (idx, token) in tokens.iter().enumerate() {
+        let gap = match spacing {
+            Spacing::None => "",
+            Spacing::Single => " ",
+            Spacing::Random => *["", "", " ", "  ", "\t"].choose(rng).unwrap(),
+        };
+        // Spaces inside a number would change the expression
+        let between_numbers = idx > 0 && tokens[idx - 1].ends_with(|a: char| a.is_ascii_digit())
+            && token.starts_with(|a: char| a.is_ascii_digit());
+        if idx > 0 || matches!(spacing, Spacing::Random) {
+            result += if between_numbers && gap.is_empty() { " " } else { gap };
+        }
+        result += token;
+    }
+    result
+}
+
+/// Drops or duplicates a token, or inserts a stray symbol
+fn corrupt(rng: &mut dyn RngCore, tokens: &mut Vec<String>) {
+    let idx = rng.gen_range(0..tokens.len());
+    match rng.gen_range(0..4) {
+        0 => {
+            tokens.remove(idx);
+        }
+        1 => tokens.insert(idx, tokens[idx].clone()),
+        2 => tokens.insert(idx, ["(", ")", "*", "/", "+"].choose(rng).unwrap().to_string()),
+        _ => tokens.insert(idx, ["$", "x", "=", "."].choose(rng).unwrap().to_string()),
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Shape {
+    depth: usize,
+    max_number: i64,
+    spacing: Spacing,
+    /// One token is broken, the result is usually a syntax error
+    corrupted: bool,
+}
+
+pub struct CalcSupplier {
+    scale: f64,
+}
+
+impl CalcSupplier {
+    pub fn new(scale: f64) -> CalcSupplier {
+        CalcSupplier { scale }
+    }
+
+    fn hand_made(&self, node: &mut TestsNode<CalcInput, CalcAnswer>) {
+        node.child("precedence")
+            .add_test(make_test("2 + 3 * 4"))
+            .add_test(make_test("(2 + 3) * 4"))
+            .add_test(make_test("10 - 4 - 3"))
+            .add_test(make_test("100 / 10 / 5"))
+            .add_test(make_test("2 * 3 / 4"))
+            .add_test(make_test("7 / 2 * 2"));
+        node.child("unary minus")
+            .add_test(make_test("-3"))
+            .add_test(make_test("-(2 + 3) * 2"))
+            .add_test(make_test("-(-3)"))
+            .add_test(make_test("2 * (-3)"))
+            .add_test(make_test("2 - -3"))
+            .add_test(make_test("-7 / 2"));
+        node.child("parentheses")
+            .add_test(make_test("((((1))))"))
+            .add_test(make_test("(1 + (2 * (3 + (4 * (5 + 6)))))"))
+            .add_test(make_test("(1) + (2)"));
+        node.child("whitespace")
+            .add_test(make_test("1+2*3"))
+            .add_test(make_test("   1 \t+  2   "))
+            .add_test(make_test("\t(\t1\t)\t"));
+        node.child("division by zero")
+            .add_test(make_test("1 / 0"))
+            .add_test(make_test("5 / (3 - 3)"))
+            .add_test(make_test("0 / 0"))
+            .add_test(make_test("1 + 2 * (4 / (2 * 2 - 4))"));
+        node.child("syntax errors")
+            .add_test(make_test(""))
+            .add_test(make_test("   "))
+            .add_test(make_test("1 +"))
+            .add_test(make_test("* 2"))
+            .add_test(make_test("(1 + 2"))
+            .add_test(make_test("1 + 2)"))
+            .add_test(make_test("()"))
+            .add_test(make_test("1 2"))
+            .add_test(make_test("2 (3)"))
+            .add_test(make_test("1 $ 2"))
+            .add_test(make_test("+5"))
+            .add_test(make_test("1 / 0 +"));
+        node.child("large numbers")
+            .add_test(make_test("1000000000 * 1000000000"))
+            .add_test(make_test("-9223372036854775807 - 1"));
+    }
+
+    fn random_group(&self, node: &mut TestsNode<CalcInput, CalcAnswer>, rng: &mut dyn RngCore, num: usize, shape: Shape) {
+        let mut count = 0;
+        while count < scale_count(num, self.scale) {
+            let mut tokens = Vec::new();
+            random_expr(rng, shape.depth, shape.max_number).tokens(&mut tokens);
+            if shape.corrupted {
+                corrupt(rng, &mut tokens);
+            }
+            if let Some(test) = try_make_test(join_tokens(rng, &tokens, shape.spacing)) {
+                node.add_test(test);
+                count += 1;
+            }
+        }
+    }
+}
+
+impl TestsSupplier<CalcInput, CalcAnswer> for CalcSupplier {
+    fn supply_tests(&self, rng: &mut dyn RngCore) -> TestsNode<CalcInput, CalcAnswer> {
+        let mut result = TestsNode::new("root");
+
+        self.hand_made(result.child("hand-made"));
+
+        let small = Shape { depth: 3, max_number: 9, spacing: Spacing::Single, corrupted: false };
+        self.random_group(result.child("small"), rng, 100, small);
+        self.random_group(result.child("no spaces"), rng, 50, Shape { depth: 4, max_number: 100, spacing: Spacing::None, ..small });
+        self.random_group(
+            result.child("random whitespace"), rng, 50, Shape { depth: 4, max_number: 100, spacing: Spacing::Random, ..small }
+        );
+        self.random_group(result.child("deep nesting"), rng, 30, Shape { depth: 12, max_number: 1000, ..small });
+        self.random_group(
+            result.child("malformed"), rng, 100,
+            Shape { depth: 4, max_number: 100, spacing: Spacing::Random, corrupted: true },
+        );
+
+        result
+    }
+}
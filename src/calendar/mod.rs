@@ -0,0 +1,330 @@
+use std::fmt::{Display, Formatter};
+
+use rand::{Rng, RngCore};
+use rand::seq::SliceRandom;
+
+use crate::base::runner::{TryFromVerbal, VerbalInput};
+use crate::base::scale::scale_count;
+use crate::base::tests_supply::{Test, TestsNode, TestsSupplier};
+
+const WEEKDAYS: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+
+/// Proleptic Gregorian date limited to years 1..=9999
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Date {
+    year: i64,
+    month: i64,
+    day: i64,
+}
+
+fn is_leap(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn month_length(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Parses exactly `digits` ASCII digits
+fn fixed_number(s: &str, digits: usize) -> Option<i64> {
+    if s.len() != digits || !s.bytes().all(|a| a.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+impl Date {
+    fn new(year: i64, month: i64, day: i64) -> Option<Date> {
+        let valid = (1..=9999).contains(&year) && (1..=12).contains(&month)
+            && (1..=month_length(year, month)).contains(&day);
+        if valid { Some(Date { year, month, day }) } else { None }
+    }
+
+    /// Strict `YYYY-MM-DD`
+    fn parse(s: &str) -> Option<Date> {
+        let parts: Vec<&str> = s.split('-').collect();
+        if parts.len() != 3 {
+            return None;
+        }
+        Date::new(fixed_number(parts[0], 4)?, fixed_number(parts[1], 2)?, fixed_number(parts[2], 2)?)
+    }
+
+    /// Days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html
+    fn to_days(self) -> i64 {
+        let year = if self.month <= 2 { self.year - 1 } else { self.year };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((self.month + 9) % 12) + 2) / 5 + self.day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146097 + day_of_era - 719468
+    }
+
+    fn from_days(days: i64) -> Option<Date> {
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days - era * 146097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+        Date::new(year, month, day)
+    }
+
+    fn weekday(self) -> &'static str {
+        WEEKDAYS[(self.to_days() + 3).rem_euclid(7) as usize]
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// `HH:MM` for times and `+HH:MM`/`-HH:MM` for UTC offsets, both in minutes
+fn parse_time(s: &str) -> Option<i64> {
+    let (hours, minutes) = s.split_once(':')?;
+    let (hours, minutes) = (fixed_number(hours, 2)?, fixed_number(minutes, 2)?);
+    if hours < 24 && minutes < 60 { Some(hours * 60 + minutes) } else { None }
+}
+
+fn parse_offset(s: &str) -> Option<i64> {
+    let sign = match s.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let offset = sign * parse_time(&s[1..])?;
+    if (-12 * 60..=14 * 60).contains(&offset) { Some(offset) } else { None }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// `weekday <date>`
+    Weekday,
+    /// `diff <from> <to>`, signed number of days
+    Diff,
+    /// `add <date> <days>`
+    Add,
+    /// `convert <date> <time> <from offset> <to offset>`
+    Convert,
+}
+
+impl Operation {
+    fn name(&self) -> &'static str {
+        match self {
+            Operation::Weekday => "weekday",
+            Operation::Diff => "diff",
+            Operation::Add => "add",
+            Operation::Convert => "convert",
+        }
+    }
+}
+
+/// Arguments are kept as text, so that invalid dates can be fed as is
+#[derive(Clone)]
+pub struct CalendarInput {
+    operation: Operation,
+    args: Vec<String>,
+}
+
+impl CalendarInput {
+    fn evaluate(&self) -> Option<String> {
+        let args = &self.args;
+        match self.operation {
+            Operation::Weekday => Some(Date::parse(&args[0])?.weekday().to_string()),
+            Operation::Diff => Some((Date::parse(&args[1])?.to_days() - Date::parse(&args[0])?.to_days()).to_string()),
+            Operation::Add => {
+                let days: i64 = args[1].parse().ok()?;
+                Some(Date::from_days(Date::parse(&args[0])?.to_days() + days)?.to_string())
+            }
+            Operation::Convert => {
+                let date = Date::parse(&args[0])?;
+                let time = parse_time(&args[1])?;
+                let minutes = date.to_days() * 1440 + time - parse_offset(&args[2])? + parse_offset(&args[3])?;
+                let date = Date::from_days(minutes.div_euclid(1440))?;
+                let time = minutes.rem_euclid(1440);
+                Some(format!("{} {:02}:{:02}", date, time / 60, time % 60))
+            }
+        }
+    }
+}
+
+impl Display for CalendarInput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} {}", self.operation.name(), self.args.join(" "))
+    }
+}
+
+impl VerbalInput for CalendarInput {
+    fn is_output_complete(&self, output: &[u8]) -> bool {
+        output.ends_with(b"\n")
+    }
+}
+
+#[derive(Clone)]
+pub enum CalendarAnswer {
+    Value(String),
+    Error(String),
+}
+
+/// Weekday names are case-insensitive, any message starting with `error` matches an expected error
+impl PartialEq for CalendarAnswer {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (CalendarAnswer::Value(a), CalendarAnswer::Value(b)) => a.eq_ignore_ascii_case(b),
+            (CalendarAnswer::Error(_), CalendarAnswer::Error(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for CalendarAnswer {}
+
+impl TryFromVerbal for CalendarAnswer {
+    fn try_from(s: &str) -> Result<Self, String> {
+        let s = s.split_whitespace().collect::<Vec<&str>>().join(" ");
+        if s.to_lowercase().starts_with("error") {
+            return Ok(CalendarAnswer::Error(s));
+        }
+        Ok(CalendarAnswer::Value(s))
+    }
+}
+
+impl Display for CalendarAnswer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CalendarAnswer::Value(a) => f.write_str(a),
+            CalendarAnswer::Error(e) => f.write_str(e),
+        }
+    }
+}
+
+fn make_test(operation: Operation, args: &[&str]) -> Test<CalendarInput, CalendarAnswer> {
+    let input = CalendarInput { operation, args: args.iter().map(|a| a.to_string()).collect() };
+    let answer = match input.evaluate() {
+        Some(value) => CalendarAnswer::Value(value),
+        None => CalendarAnswer::Error("error: invalid input".to_string()),
+    };
+    Test::new(input, answer)
+}
+
+fn random_date(rng: &mut dyn RngCore) -> Date {
+    let year = if rng.gen_bool(0.5) { rng.gen_range(1900..2100) } else { rng.gen_range(1..=9999) };
+    let month = rng.gen_range(1..=12);
+    Date { year, month, day: rng.gen_range(1..=month_length(year, month)) }
+}
+
+/// First or last days of months, leap days and century years
+fn random_boundary_date(rng: &mut dyn RngCore) -> Date {
+    let year = *[1600, 1700, 1900, 2000, 2023, 2024, 2100, 2400].choose(rng).unwrap();
+    let month = rng.gen_range(1..=12);
+    let day = if rng.gen_bool(0.5) { 1 } else { month_length(year, month) };
+    Date { year, month, day }
+}
+
+fn random_offset(rng: &mut dyn RngCore) -> String {
+    let offset: i64 = rng.gen_range(-24..=28) * 30;
+    format!("{}{:02}:{:02}", if offset < 0 { '-' } else { '+' }, offset.abs() / 60, offset.abs() % 60)
+}
+
+fn random_test(rng: &mut dyn RngCore, date: &mut dyn FnMut(&mut dyn RngCore) -> Date) -> Test<CalendarInput, CalendarAnswer> {
+    let a = date(rng).to_string();
+    match rng.gen_range(0..4) {
+        0 => make_test(Operation::Weekday, &[&a]),
+        1 => make_test(Operation::Diff, &[&a, &date(rng).to_string()]),
+        2 => make_test(Operation::Add, &[&a, &rng.gen_range(-1000..=1000).to_string()]),
+        _ => {
+            let time = format!("{:02}:{:02}", rng.gen_range(0..24), rng.gen_range(0..60));
+            make_test(Operation::Convert, &[&a, &time, &random_offset(rng), &random_offset(rng)])
+        }
+    }
+}
+
+pub struct CalendarSupplier {
+    scale: f64,
+}
+
+impl CalendarSupplier {
+    pub fn new(scale: f64) -> CalendarSupplier {
+        CalendarSupplier { scale }
+    }
+
+    fn hand_made(&self, node: &mut TestsNode<CalendarInput, CalendarAnswer>) {
+        node.child("leap years")
+            .add_test(make_test(Operation::Weekday, &["2024-02-29"]))
+            .add_test(make_test(Operation::Weekday, &["2023-02-29"]))
+            .add_test(make_test(Operation::Add, &["2024-02-28", "1"]))
+            .add_test(make_test(Operation::Add, &["2023-02-28", "1"]))
+            .add_test(make_test(Operation::Diff, &["2024-01-01", "2025-01-01"]));
+        node.child("century rules")
+            .add_test(make_test(Operation::Weekday, &["2000-02-29"]))
+            .add_test(make_test(Operation::Weekday, &["1900-02-29"]))
+            .add_test(make_test(Operation::Weekday, &["2100-02-29"]))
+            .add_test(make_test(Operation::Diff, &["1900-01-01", "2000-01-01"]))
+            .add_test(make_test(Operation::Add, &["1900-02-28", "1"]));
+        node.child("month boundaries")
+            .add_test(make_test(Operation::Add, &["2023-01-31", "1"]))
+            .add_test(make_test(Operation::Add, &["2023-12-31", "1"]))
+            .add_test(make_test(Operation::Add, &["2023-03-01", "-1"]))
+            .add_test(make_test(Operation::Add, &["2023-05-15", "0"]))
+            .add_test(make_test(Operation::Diff, &["2023-03-01", "2023-02-28"]))
+            .add_test(make_test(Operation::Diff, &["0001-01-01", "9999-12-31"]));
+        node.child("invalid dates")
+            .add_test(make_test(Operation::Weekday, &["2023-13-01"]))
+            .add_test(make_test(Operation::Weekday, &["2023-00-10"]))
+            .add_test(make_test(Operation::Weekday, &["2023-04-31"]))
+            .add_test(make_test(Operation::Weekday, &["2023-01-00"]))
+            .add_test(make_test(Operation::Weekday, &["0000-01-01"]))
+            .add_test(make_test(Operation::Weekday, &["2023/01/01"]))
+            .add_test(make_test(Operation::Weekday, &["2023-1-1"]))
+            .add_test(make_test(Operation::Diff, &["2023-01-01", "2023-02-30"]))
+            .add_test(make_test(Operation::Add, &["9999-12-31", "1"]))
+            .add_test(make_test(Operation::Add, &["0001-01-01", "-1"]));
+        node.child("time zones")
+            .add_test(make_test(Operation::Convert, &["2023-12-31", "23:30", "+00:00", "+01:00"]))
+            .add_test(make_test(Operation::Convert, &["2024-03-01", "00:15", "+03:00", "+00:00"]))
+            .add_test(make_test(Operation::Convert, &["2023-06-15", "12:00", "-09:30", "+05:45"]))
+            .add_test(make_test(Operation::Convert, &["2023-06-15", "24:00", "+00:00", "+00:00"]))
+            .add_test(make_test(Operation::Convert, &["2023-06-15", "12:00", "+15:00", "+00:00"]));
+    }
+}
+
+impl TestsSupplier<CalendarInput, CalendarAnswer> for CalendarSupplier {
+    fn supply_tests(&self, rng: &mut dyn RngCore) -> TestsNode<CalendarInput, CalendarAnswer> {
+        let mut result = TestsNode::new("root");
+
+        self.hand_made(result.child("hand-made"));
+
+        let node = result.child("random dates");
+        for _ in 0..scale_count(200, self.scale) {
+            node.add_test(random_test(rng, &mut random_date));
+        }
+
+        let node = result.child("boundaries");
+        for _ in 0..scale_count(100, self.scale) {
+            node.add_test(random_test(rng, &mut random_boundary_date));
+        }
+
+        let node = result.child("invalid dates");
+        for _ in 0..scale_count(50, self.scale) {
+            let date = random_boundary_date(rng);
+            let (month, day) = if rng.gen_bool(0.5) {
+                (date.month, month_length(date.year, date.month) + 1)
+            } else {
+                (rng.gen_range(13..=99), date.day)
+            };
+            let invalid = format!("{:04}-{:02}-{:02}", date.year, month, day);
+            node.add_test(make_test(Operation::Weekday, &[&invalid]));
+        }
+
+        result
+    }
+}
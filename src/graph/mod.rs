@@ -0,0 +1,434 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+
+use rand::{Rng, RngCore};
+use rand::seq::SliceRandom;
+
+use crate::base::runner::{TryFromVerbal, VerbalInput};
+use crate::base::scale::scale_count;
+use crate::base::tests_supply::{Test, TestsNode, TestsSupplier};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Task {
+    /// Undirected weighted graph, distance and any shortest path between two vertices or `-1`
+    ShortestPath,
+    /// Undirected graph, component count and a component label for every vertex
+    Components,
+    /// Directed graph, any topological order or `cycle`
+    TopologicalSort,
+}
+
+impl Task {
+    fn name(&self) -> &'static str {
+        match self {
+            Task::ShortestPath => "path",
+            Task::Components => "components",
+            Task::TopologicalSort => "toposort",
+        }
+    }
+}
+
+/// Vertices are numbered from 1
+#[derive(Clone)]
+pub struct Graph {
+    task: Task,
+    n: usize,
+    edges: Vec<(usize, usize, u64)>,
+    /// Source and target for `ShortestPath`
+    query: (usize, usize),
+    answer_lines: usize,
+}
+
+impl Display for Graph {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} {} {}", self.task.name(), self.n, self.edges.len())?;
+        for (u, v, w) in &self.edges {
+            match self.task {
+                Task::ShortestPath => writeln!(f, "{} {} {}", u, v, w)?,
+                _ => writeln!(f, "{} {}", u, v)?,
+            }
+        }
+        if self.task == Task::ShortestPath {
+            writeln!(f, "{} {}", self.query.0, self.query.1)?;
+        }
+        Ok(())
+    }
+}
+
+impl VerbalInput for Graph {
+    fn is_output_complete(&self, output: &[u8]) -> bool {
+        output.iter().filter(|a| **a == b'\n').count() >= self.answer_lines
+    }
+}
+
+impl Graph {
+    fn adjacency(&self) -> Vec<Vec<(usize, u64)>> {
+        let mut result = vec![Vec::new(); self.n + 1];
+        for (u, v, w) in &self.edges {
+            result[*u].push((*v, *w));
+            if self.task != Task::TopologicalSort {
+                result[*v].push((*u, *w));
+            }
+        }
+        result
+    }
+
+    /// Lightest edge for every connected pair, in both directions
+    fn lightest_edges(&self) -> HashMap<(usize, usize), u64> {
+        let mut result = HashMap::new();
+        for (u, v, w) in &self.edges {
+            for key in [(*u, *v), (*v, *u)] {
+                let entry = result.entry(key).or_insert(*w);
+                *entry = (*entry).min(*w);
+            }
+        }
+        result
+    }
+
+    fn shortest_path(&self) -> Vec<String> {
+        let adjacency = self.adjacency();
+        let (source, target) = self.query;
+        let mut distance = vec![u64::MAX; self.n + 1];
+        let mut parent = vec![0; self.n + 1];
+        let mut queue = BinaryHeap::new();
+        distance[source] = 0;
+        queue.push(Reverse((0, source)));
+        while let Some(Reverse((d, u))) = queue.pop() {
+            if d > distance[u] {
+                continue;
+            }
+            for (v, w) in &adjacency[u] {
+                if d + w < distance[*v] {
+                    distance[*v] = d + w;
+                    parent[*v] = u;
+                    queue.push(Reverse((d + w, *v)));
+                }
+            }
+        }
+        if distance[target] == u64::MAX {
+            return vec!["-1".to_string()];
+        }
+        let mut path = vec![target];
+        while *path.last().unwrap() != source {
+            path.push(parent[*path.last().unwrap()]);
+        }
+        let path: Vec<String> = path.iter().rev().map(|a| a.to_string()).collect();
+        vec![distance[target].to_string(), path.join(" ")]
+    }
+
+    fn component_labels(&self) -> Vec<usize> {
+        let adjacency = self.adjacency();
+        let mut labels = vec![0; self.n + 1];
+        let mut count = 0;
+        for start in 1..=self.n {
+            if labels[start] != 0 {
+                continue;
+            }
+            count += 1;
+            labels[start] = count;
+            let mut queue = VecDeque::from(vec![start]);
+            while let Some(u) = queue.pop_front() {
+                for (v, _) in &adjacency[u] {
+                    if labels[*v] == 0 {
+                        labels[*v] = count;
+                        queue.push_back(*v);
+                    }
+                }
+            }
+        }
+        labels
+    }
+
+    fn components(&self) -> Vec<String> {
+        let labels = self.component_labels();
+        let count = labels.iter().max().copied().unwrap_or(0);
+        let labels: Vec<String> = labels[1..].iter().map(|a| a.to_string()).collect();
+        vec![count.to_string(), labels.join(" ")]
+    }
+
+    fn topological_sort(&self) -> Vec<String> {
+        let adjacency = self.adjacency();
+        let mut in_degree = vec![0; self.n + 1];
+        for (_, v, _) in &self.edges {
+            in_degree[*v] += 1;
+        }
+        let mut queue: VecDeque<usize> = (1..=self.n).filter(|a| in_degree[*a] == 0).collect();
+        let mut order = Vec::new();
+        while let Some(u) = queue.pop_front() {
+            order.push(u.to_string());
+            for (v, _) in &adjacency[u] {
+                in_degree[*v] -= 1;
+                if in_degree[*v] == 0 {
+                    queue.push_back(*v);
+                }
+            }
+        }
+        if order.len() < self.n {
+            return vec!["cycle".to_string()];
+        }
+        vec![order.join(" ")]
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct GraphAnswer {
+    lines: Vec<String>,
+}
+
+impl TryFromVerbal for GraphAnswer {
+    fn try_from(s: &str) -> Result<Self, String> {
+        let lines = s.lines()
+            .map(|a| a.split_whitespace().collect::<Vec<&str>>().join(" "))
+            .filter(|a| !a.is_empty())
+            .collect();
+        Ok(GraphAnswer { lines })
+    }
+}
+
+impl Display for GraphAnswer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+fn line<'a>(answer: &'a GraphAnswer, idx: usize, what: &str) -> Result<&'a str, String> {
+    answer.lines.get(idx).map(|a| a.as_str()).ok_or_else(|| format!("Missing {}", what))
+}
+
+fn numbers(line: &str, what: &str) -> Result<Vec<usize>, String> {
+    line.split(' ')
+        .map(|a| a.parse().map_err(|_| format!("Cannot parse {} '{}'", what, a)))
+        .collect()
+}
+
+fn check_path(input: &Graph, expected: &GraphAnswer, actual: &GraphAnswer) -> Result<(), String> {
+    let expected_distance = line(expected, 0, "distance")?;
+    let actual_distance = line(actual, 0, "distance")?;
+    if expected_distance != actual_distance {
+        return Err(format!("Expected distance {} but got {}", expected_distance, actual_distance));
+    }
+    if expected_distance == "-1" {
+        return Ok(());
+    }
+
+    let path = numbers(line(actual, 1, "path")?, "vertex")?;
+    let (source, target) = input.query;
+    if path.first() != Some(&source) || path.last() != Some(&target) {
+        return Err(format!("Path must go from {} to {}", source, target));
+    }
+    let edges = input.lightest_edges();
+    let mut length = 0;
+    for pair in path.windows(2) {
+        match edges.get(&(pair[0], pair[1])) {
+            Some(w) => length += w,
+            None => return Err(format!("Path uses missing edge {} - {}", pair[0], pair[1])),
+        }
+    }
+    if length.to_string() != expected_distance {
+        return Err(format!("Path length is {} instead of {}", length, expected_distance));
+    }
+    Ok(())
+}
+
+fn check_components(input: &Graph, expected: &GraphAnswer, actual: &GraphAnswer) -> Result<(), String> {
+    let expected_count = line(expected, 0, "component count")?;
+    let actual_count = line(actual, 0, "component count")?;
+    if expected_count != actual_count {
+        return Err(format!("Expected {} components but got {}", expected_count, actual_count));
+    }
+
+    let expected_labels = input.component_labels();
+    let actual_labels = numbers(line(actual, 1, "labels")?, "label")?;
+    if actual_labels.len() != input.n {
+        return Err(format!("Expected {} labels but got {}", input.n, actual_labels.len()));
+    }
+    // Labels are arbitrary, only the partition matters
+    let mut first_with_label: HashMap<usize, usize> = HashMap::new();
+    let mut first_in_component: HashMap<usize, usize> = HashMap::new();
+    for vertex in 1..=input.n {
+        let label = actual_labels[vertex - 1];
+        let other = *first_with_label.entry(label).or_insert(vertex);
+        if expected_labels[other] != expected_labels[vertex] {
+            return Err(format!("Vertices {} and {} are not connected but have the same label", other, vertex));
+        }
+        let other = *first_in_component.entry(expected_labels[vertex]).or_insert(vertex);
+        if actual_labels[other - 1] != label {
+            return Err(format!("Vertices {} and {} are connected but have different labels", other, vertex));
+        }
+    }
+    Ok(())
+}
+
+fn check_order(input: &Graph, expected: &GraphAnswer, actual: &GraphAnswer) -> Result<(), String> {
+    let expected = line(expected, 0, "order")?;
+    let actual = line(actual, 0, "order")?;
+    if expected == "cycle" || actual.eq_ignore_ascii_case("cycle") {
+        return if expected.eq_ignore_ascii_case(actual) {
+            Ok(())
+        } else {
+            Err(format!("Expected '{}' but got '{}'", expected, actual))
+        };
+    }
+
+    let order = numbers(actual, "vertex")?;
+    let mut position = vec![usize::MAX; input.n + 1];
+    for (idx, vertex) in order.iter().enumerate() {
+        if *vertex < 1 || *vertex > input.n || position[*vertex] != usize::MAX {
+            return Err(format!("Order is not a permutation: vertex {} is unknown or repeated", vertex));
+        }
+        position[*vertex] = idx;
+    }
+    if order.len() != input.n {
+        return Err(format!("Order has {} vertices instead of {}", order.len(), input.n));
+    }
+    for (u, v, _) in &input.edges {
+        if position[*u] > position[*v] {
+            return Err(format!("Edge {} -> {} goes backwards", u, v));
+        }
+    }
+    Ok(())
+}
+
+/// Any valid answer is accepted, `expected` only holds one of them
+pub fn check(input: &Graph, expected: &GraphAnswer, actual: &GraphAnswer) -> Result<(), String> {
+    match input.task {
+        Task::ShortestPath => check_path(input, expected, actual),
+        Task::Components => check_components(input, expected, actual),
+        Task::TopologicalSort => check_order(input, expected, actual),
+    }
+}
+
+fn make_test(task: Task, n: usize, edges: Vec<(usize, usize, u64)>, query: (usize, usize)) -> Test<Graph, GraphAnswer> {
+    let mut graph = Graph { task, n, edges, query, answer_lines: 0 };
+    let lines = match task {
+        Task::ShortestPath => graph.shortest_path(),
+        Task::Components => graph.components(),
+        Task::TopologicalSort => graph.topological_sort(),
+    };
+    graph.answer_lines = lines.len();
+    Test::new(graph, GraphAnswer { lines })
+}
+
+fn unweighted(edges: &[(usize, usize)]) -> Vec<(usize, usize, u64)> {
+    edges.iter().map(|(u, v)| (*u, *v, 1)).collect()
+}
+
+#[derive(Clone, Copy)]
+struct Shape {
+    vertices: (usize, usize),
+    /// Average number of edges per vertex
+    density: f64,
+    /// Vertices are split into this many parts with no edges between them
+    parts: usize,
+    /// For directed graphs, chance of adding a back edge which makes a cycle
+    cycle_chance: f64,
+}
+
+fn random_graph(rng: &mut dyn RngCore, task: Task, shape: &Shape) -> Test<Graph, GraphAnswer> {
+    let n = rng.gen_range(shape.vertices.0..=shape.vertices.1);
+    let parts = shape.parts.min(n);
+    // Hidden numbering, so that parts and topological order are not visible in vertex numbers
+    let mut names: Vec<usize> = (1..=n).collect();
+    names.shuffle(rng);
+    let part_start = |part: usize| (part * n).div_ceil(parts);
+
+    let mut edges = Vec::new();
+    let m = (n as f64 * shape.density) as usize;
+    for _ in 0..m {
+        let part = rng.gen_range(0..parts);
+        let (start, end) = (part_start(part), part_start(part + 1));
+        if start == end {
+            continue;
+        }
+        let (u, v) = (rng.gen_range(start..end), rng.gen_range(start..end));
+        let (u, v) = if task == Task::TopologicalSort { (u.min(v), u.max(v)) } else { (u, v) };
+        if task == Task::TopologicalSort && u == v {
+            continue;
+        }
+        edges.push((names[u], names[v], rng.gen_range(0..=100)));
+    }
+    if task == Task::TopologicalSort && edges.len() > 1 && rng.gen_bool(shape.cycle_chance) {
+        let (u, v, w) = *edges.choose(rng).unwrap();
+        edges.push((v, u, w));
+    }
+    edges.shuffle(rng);
+
+    let query = (rng.gen_range(1..=n), rng.gen_range(1..=n));
+    make_test(task, n, edges, query)
+}
+
+pub struct GraphSupplier {
+    task: Task,
+    scale: f64,
+}
+
+impl GraphSupplier {
+    pub fn new(task: Task, scale: f64) -> GraphSupplier {
+        GraphSupplier { task, scale }
+    }
+
+    fn hand_made(&self, node: &mut TestsNode<Graph, GraphAnswer>) {
+        match self.task {
+            Task::ShortestPath => {
+                node.child("trivial")
+                    .add_test(make_test(Task::ShortestPath, 1, vec![], (1, 1)))
+                    .add_test(make_test(Task::ShortestPath, 2, vec![], (1, 2)))
+                    .add_test(make_test(Task::ShortestPath, 2, vec![(1, 2, 0)], (2, 1)));
+                node.child("detour is shorter")
+                    .add_test(make_test(Task::ShortestPath, 3, vec![(1, 3, 10), (1, 2, 3), (2, 3, 3)], (1, 3)));
+                node.child("parallel edges")
+                    .add_test(make_test(Task::ShortestPath, 2, vec![(1, 2, 7), (1, 2, 2), (2, 2, 1)], (1, 2)));
+                node.child("several shortest paths")
+                    .add_test(make_test(Task::ShortestPath, 4, vec![(1, 2, 1), (1, 3, 1), (2, 4, 1), (3, 4, 1)], (1, 4)));
+            }
+            Task::Components => {
+                node.child("trivial")
+                    .add_test(make_test(Task::Components, 1, vec![], (1, 1)))
+                    .add_test(make_test(Task::Components, 3, vec![], (1, 1)))
+                    .add_test(make_test(Task::Components, 3, unweighted(&[(1, 1)]), (1, 1)));
+                node.child("chain")
+                    .add_test(make_test(Task::Components, 5, unweighted(&[(1, 2), (2, 3), (3, 4), (4, 5)]), (1, 1)))
+                    .add_test(make_test(Task::Components, 5, unweighted(&[(5, 1), (2, 4)]), (1, 1)));
+            }
+            Task::TopologicalSort => {
+                node.child("trivial")
+                    .add_test(make_test(Task::TopologicalSort, 1, vec![], (1, 1)))
+                    .add_test(make_test(Task::TopologicalSort, 3, vec![], (1, 1)));
+                node.child("cycles")
+                    .add_test(make_test(Task::TopologicalSort, 1, unweighted(&[(1, 1)]), (1, 1)))
+                    .add_test(make_test(Task::TopologicalSort, 2, unweighted(&[(1, 2), (2, 1)]), (1, 1)))
+                    .add_test(make_test(Task::TopologicalSort, 4, unweighted(&[(1, 2), (2, 3), (3, 4), (4, 2)]), (1, 1)));
+                node.child("reversed chain")
+                    .add_test(make_test(Task::TopologicalSort, 4, unweighted(&[(4, 3), (3, 2), (2, 1)]), (1, 1)))
+                    .add_test(make_test(Task::TopologicalSort, 3, unweighted(&[(3, 1), (3, 2), (3, 1)]), (1, 1)));
+            }
+        }
+    }
+
+    fn random_group(&self, node: &mut TestsNode<Graph, GraphAnswer>, rng: &mut dyn RngCore, num: usize, shape: Shape) {
+        for _ in 0..scale_count(num, self.scale) {
+            node.add_test(random_graph(rng, self.task, &shape));
+        }
+    }
+}
+
+impl TestsSupplier<Graph, GraphAnswer> for GraphSupplier {
+    fn supply_tests(&self, rng: &mut dyn RngCore) -> TestsNode<Graph, GraphAnswer> {
+        let mut result = TestsNode::new("root");
+
+        self.hand_made(result.child("hand-made"));
+
+        let random = Shape { vertices: (2, 30), density: 1.5, parts: 1, cycle_chance: 0.3 };
+        self.random_group(result.child("random"), rng, 100, random);
+        self.random_group(result.child("sparse"), rng, 30, Shape { vertices: (100, 2000), density: 1.0, ..random });
+        self.random_group(result.child("dense"), rng, 20, Shape { vertices: (30, 80), density: 20.0, ..random });
+        self.random_group(result.child("disconnected"), rng, 30, Shape { vertices: (10, 200), parts: 5, ..random });
+        self.random_group(result.child("cyclic"), rng, 30, Shape { cycle_chance: 1.0, ..random });
+
+        result
+    }
+}
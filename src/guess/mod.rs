@@ -0,0 +1,95 @@
+use std::fmt::{Display, Formatter};
+
+use rand::{Rng, RngCore};
+
+use crate::base::interactive::{Dialogue, Interactor, Outcome};
+use crate::base::scale::scale_count;
+use crate::base::tests_supply::{Test, TestsNode, TestsSupplier};
+
+#[derive(Clone)]
+pub struct Game {
+    max: u64,
+    secret: u64,
+}
+
+impl Game {
+    /// Binary search never needs more
+    fn query_limit(&self) -> u32 {
+        u64::BITS - self.max.leading_zeros()
+    }
+}
+
+impl Display for Game {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Secret {} in 1..={}, at most {} guesses", self.secret, self.max, self.query_limit())
+    }
+}
+
+/// Sends `n`, then answers every guess with `less`, `greater` or `correct`
+pub struct GuessInteractor;
+
+impl Interactor<Game> for GuessInteractor {
+    fn interact(&self, game: &Game, dialogue: &mut Dialogue) -> Result<(), String> {
+        dialogue.send(&game.max.to_string())?;
+        for _ in 0..game.query_limit() {
+            let line = dialogue.receive()?;
+            let guess: u64 = line.trim().parse().map_err(|_| format!("Cannot parse guess '{}'", line))?;
+            if guess < 1 || guess > game.max {
+                return Err(format!("Guess {} is out of range 1..={}", guess, game.max));
+            }
+            if guess == game.secret {
+                return dialogue.send("correct");
+            }
+            dialogue.send(if game.secret < guess { "less" } else { "greater" })?;
+        }
+        Err(format!("Number is not guessed in {} queries", game.query_limit()))
+    }
+}
+
+fn make_test(max: u64, secret: u64) -> Test<Game, Outcome> {
+    Test::new(Game { max, secret }, Outcome::Accepted)
+}
+
+pub struct GuessSupplier {
+    scale: f64,
+}
+
+impl GuessSupplier {
+    pub fn new(scale: f64) -> GuessSupplier {
+        GuessSupplier { scale }
+    }
+
+    fn random_group(&self, node: &mut TestsNode<Game, Outcome>, rng: &mut dyn RngCore, num: usize, max: u64) {
+        for _ in 0..scale_count(num, self.scale) {
+            let max = rng.gen_range(1..=max);
+            node.add_test(make_test(max, rng.gen_range(1..=max)));
+        }
+    }
+}
+
+impl TestsSupplier<Game, Outcome> for GuessSupplier {
+    fn supply_tests(&self, rng: &mut dyn RngCore) -> TestsNode<Game, Outcome> {
+        let mut result = TestsNode::new("root");
+
+        let hand_made = result.child("hand-made");
+        hand_made.child("single number")
+            .add_test(make_test(1, 1));
+        hand_made.child("bounds")
+            .add_test(make_test(2, 1))
+            .add_test(make_test(2, 2))
+            .add_test(make_test(1000, 1))
+            .add_test(make_test(1000, 1000))
+            .add_test(make_test(1023, 512))
+            .add_test(make_test(1024, 1024));
+        hand_made.child("large range")
+            .add_test(make_test(1_000_000_000_000, 1))
+            .add_test(make_test(1_000_000_000_000, 999_999_999_999))
+            .add_test(make_test(u32::MAX as u64 + 1, u32::MAX as u64 + 1));
+
+        self.random_group(result.child("small"), rng, 30, 100);
+        self.random_group(result.child("medium"), rng, 20, 1_000_000);
+        self.random_group(result.child("large"), rng, 10, 1_000_000_000_000_000);
+
+        result
+    }
+}
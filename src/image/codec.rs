@@ -0,0 +1,216 @@
+use std::convert::TryInto;
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Bmp,
+    /// Binary PPM, P6
+    Ppm,
+    /// Binary PGM, P5
+    Pgm,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Bmp => "bmp",
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Pgm => "pgm",
+        }
+    }
+}
+
+impl Display for ImageFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.extension())
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    /// Row-major RGB, top row first
+    pub pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Image {
+        Image { width, height, pixels: vec![[0; 3]; width * height] }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> [u8; 3] {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, pixel: [u8; 3]) {
+        self.pixels[y * self.width + x] = pixel;
+    }
+}
+
+fn bmp_row_size(width: usize) -> usize {
+    (width * 3).div_ceil(4) * 4
+}
+
+pub fn encode(image: &Image, format: ImageFormat) -> Vec<u8> {
+    match format {
+        ImageFormat::Bmp => {
+            let row_size = bmp_row_size(image.width);
+            let data_size = row_size * image.height;
+            let mut result = Vec::with_capacity(54 + data_size);
+            result.extend_from_slice(b"BM");
+            result.extend_from_slice(&((54 + data_size) as u32).to_le_bytes());
+            result.extend_from_slice(&[0; 4]);
+            result.extend_from_slice(&54u32.to_le_bytes());
+            result.extend_from_slice(&40u32.to_le_bytes());
+            result.extend_from_slice(&(image.width as i32).to_le_bytes());
+            result.extend_from_slice(&(image.height as i32).to_le_bytes());
+            result.extend_from_slice(&1u16.to_le_bytes());
+            result.extend_from_slice(&24u16.to_le_bytes());
+            result.extend_from_slice(&0u32.to_le_bytes());
+            result.extend_from_slice(&(data_size as u32).to_le_bytes());
+            result.extend_from_slice(&2835i32.to_le_bytes());
+            result.extend_from_slice(&2835i32.to_le_bytes());
+            result.extend_from_slice(&[0; 8]);
+            for y in (0..image.height).rev() {
+                for x in 0..image.width {
+                    let [r, g, b] = image.get(x, y);
+                    result.extend_from_slice(&[b, g, r]);
+                }
+                result.resize(result.len() + row_size - image.width * 3, 0);
+            }
+            result
+        }
+        ImageFormat::Ppm => {
+            let mut result = format!("P6\n{} {}\n255\n", image.width, image.height).into_bytes();
+            image.pixels.iter().for_each(|a| result.extend_from_slice(a));
+            result
+        }
+        ImageFormat::Pgm => {
+            let mut result = format!("P5\n{} {}\n255\n", image.width, image.height).into_bytes();
+            image.pixels.iter().for_each(|a| result.push(a[0]));
+            result
+        }
+    }
+}
+
+fn read_u32(data: &[u8], at: usize) -> Result<u32, String> {
+    data.get(at..at + 4)
+        .map(|a| u32::from_le_bytes(a.try_into().unwrap()))
+        .ok_or_else(|| "BMP header is truncated".to_string())
+}
+
+fn read_u16(data: &[u8], at: usize) -> Result<u16, String> {
+    data.get(at..at + 2)
+        .map(|a| u16::from_le_bytes(a.try_into().unwrap()))
+        .ok_or_else(|| "BMP header is truncated".to_string())
+}
+
+fn decode_bmp(data: &[u8]) -> Result<Image, String> {
+    let offset = read_u32(data, 10)? as usize;
+    let width = read_u32(data, 18)? as i32;
+    let height = read_u32(data, 22)? as i32;
+    let bits = read_u16(data, 28)?;
+    let compression = read_u32(data, 30)?;
+    if bits != 24 && bits != 32 {
+        return Err(format!("Only 24 and 32 bit BMP are supported, found {} bits", bits));
+    }
+    if compression != 0 && !(bits == 32 && compression == 3) {
+        return Err(format!("Compressed BMP is not supported, compression is {}", compression));
+    }
+    if width <= 0 || height == 0 {
+        return Err(format!("Invalid BMP size {}x{}", width, height));
+    }
+
+    let (width, top_down) = (width as usize, height < 0);
+    let height = height.unsigned_abs() as usize;
+    let bytes = bits as usize / 8;
+    // The header comes from the program, so sizes are checked against the data before anything is allocated
+    let row_size = width.checked_mul(bytes).map(|a| a.div_ceil(4) * 4);
+    let (row_size, size) = match row_size.and_then(|a| Some((a, a.checked_mul(height)?))) {
+        Some((row_size, size)) if offset.checked_add(size).is_some() => (row_size, size),
+        _ => return Err(format!("Invalid BMP size {}x{}: pixel data does not fit in memory", width, height)),
+    };
+    if data.len() < offset + size {
+        return Err(format!(
+            "BMP pixel data is truncated: expected {} bytes of rows padded to 4 bytes, found {}",
+            size, data.len().saturating_sub(offset)
+        ));
+    }
+
+    let mut image = Image::new(width, height);
+    for row in 0..height {
+        let y = if top_down { row } else { height - 1 - row };
+        let start = offset + row * row_size;
+        for x in 0..width {
+            let pixel = &data[start + x * bytes..start + x * bytes + 3];
+            image.set(x, y, [pixel[2], pixel[1], pixel[0]]);
+        }
+    }
+    Ok(image)
+}
+
+fn decode_pnm(data: &[u8]) -> Result<Image, String> {
+    let mut header = Vec::new();
+    let mut idx = 0;
+    while header.len() < 4 {
+        while idx < data.len() && data[idx].is_ascii_whitespace() {
+            idx += 1;
+        }
+        if idx < data.len() && data[idx] == b'#' {
+            while idx < data.len() && data[idx] != b'\n' {
+                idx += 1;
+            }
+            continue;
+        }
+        let start = idx;
+        while idx < data.len() && !data[idx].is_ascii_whitespace() {
+            idx += 1;
+        }
+        if start == idx {
+            return Err("PNM header is truncated".to_string());
+        }
+        header.push(String::from_utf8_lossy(&data[start..idx]).to_string());
+    }
+    // Exactly one whitespace separates the header from the raster
+    idx += 1;
+
+    let parse = |s: &str| s.parse::<usize>().map_err(|a| format!("Invalid PNM header value '{}': {}", s, a));
+    let (width, height, max) = (parse(&header[1])?, parse(&header[2])?, parse(&header[3])?);
+    if max != 255 {
+        return Err(format!("Only 8 bit PNM is supported, max value is {}", max));
+    }
+    let channels = match header[0].as_str() {
+        "P6" => 3,
+        "P5" => 1,
+        magic => return Err(format!("Unsupported PNM type '{}'", magic)),
+    };
+    let size = match width.checked_mul(height).and_then(|a| a.checked_mul(channels)) {
+        Some(size) => size,
+        None => return Err(format!("Invalid PNM size {}x{}: raster does not fit in memory", width, height)),
+    };
+    let raster = data.get(idx..).unwrap_or(&[]);
+    if raster.len() < size {
+        return Err(format!("PNM raster is truncated: expected {} bytes, found {}", size, raster.len()));
+    }
+
+    let mut image = Image::new(width, height);
+    for (i, pixel) in image.pixels.iter_mut().enumerate() {
+        *pixel = if channels == 3 {
+            [raster[i * 3], raster[i * 3 + 1], raster[i * 3 + 2]]
+        } else {
+            [raster[i]; 3]
+        };
+    }
+    Ok(image)
+}
+
+pub fn decode(data: &[u8]) -> Result<Image, String> {
+    if data.starts_with(b"BM") {
+        decode_bmp(data)
+    } else if data.starts_with(b"P") {
+        decode_pnm(data)
+    } else {
+        Err("Unknown image format, expected BMP or binary PNM".to_string())
+    }
+}
@@ -0,0 +1,298 @@
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::process::Command;
+
+use rand::{Rng, RngCore};
+
+use crate::base::runner::{Listener, Runner};
+use crate::base::scale::scale_count;
+use crate::base::tests_supply::{Test, TestsNode, TestsSupplier};
+use crate::base::workdir::WorkDir;
+use crate::image::codec::{Image, ImageFormat};
+
+pub mod codec;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Crop { x: usize, y: usize, width: usize, height: usize },
+    /// Clockwise, in degrees
+    Rotate(u32),
+    Invert,
+    Grayscale,
+}
+
+impl Operation {
+    fn args(&self) -> Vec<String> {
+        match self {
+            Operation::Crop { x, y, width, height } => {
+                vec!["crop".to_string(), x.to_string(), y.to_string(), width.to_string(), height.to_string()]
+            }
+            Operation::Rotate(angle) => vec!["rotate".to_string(), angle.to_string()],
+            Operation::Invert => vec!["invert".to_string()],
+            Operation::Grayscale => vec!["grayscale".to_string()],
+        }
+    }
+
+    /// Students are free to round the luminance either way
+    fn tolerance(&self) -> u8 {
+        match self {
+            Operation::Grayscale => 1,
+            _ => 0,
+        }
+    }
+
+    fn apply(&self, image: &Image) -> Image {
+        match *self {
+            Operation::Crop { x, y, width, height } => {
+                let mut result = Image::new(width, height);
+                for dy in 0..height {
+                    for dx in 0..width {
+                        result.set(dx, dy, image.get(x + dx, y + dy));
+                    }
+                }
+                result
+            }
+            Operation::Rotate(angle) => {
+                let (w, h) = (image.width, image.height);
+                let mut result = if angle == 180 { Image::new(w, h) } else { Image::new(h, w) };
+                for y in 0..h {
+                    for x in 0..w {
+                        let (nx, ny) = match angle {
+                            90 => (h - 1 - y, x),
+                            180 => (w - 1 - x, h - 1 - y),
+                            _ => (y, w - 1 - x),
+                        };
+                        result.set(nx, ny, image.get(x, y));
+                    }
+                }
+                result
+            }
+            Operation::Invert => Image {
+                pixels: image.pixels.iter().map(|[r, g, b]| [255 - r, 255 - g, 255 - b]).collect(),
+                ..image.clone()
+            },
+            Operation::Grayscale => Image {
+                pixels: image.pixels.iter().map(|[r, g, b]| {
+                    let gray = (299 * *r as u32 + 587 * *g as u32 + 114 * *b as u32 + 500) / 1000;
+                    [gray as u8; 3]
+                }).collect(),
+                ..image.clone()
+            },
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ImageInput {
+    format: ImageFormat,
+    image: Image,
+    operation: Operation,
+}
+
+impl Display for ImageInput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} on {}x{} {} image", self.operation.args().join(" "), self.image.width, self.image.height, self.format)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub enum ImageOutput {
+    Decoded(Image),
+    Failed(String),
+}
+
+impl Display for ImageOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageOutput::Decoded(image) => write!(f, "{}x{} image", image.width, image.height),
+            ImageOutput::Failed(e) => f.write_str(e),
+        }
+    }
+}
+
+fn hex(pixel: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", pixel[0], pixel[1], pixel[2])
+}
+
+pub fn check(input: &ImageInput, expected: &ImageOutput, actual: &ImageOutput) -> Result<(), String> {
+    let (expected, actual) = match (expected, actual) {
+        (ImageOutput::Decoded(expected), ImageOutput::Decoded(actual)) => (expected, actual),
+        (_, ImageOutput::Failed(e)) => return Err(e.clone()),
+        _ => return Err("Reference image is missing".to_string()),
+    };
+
+    if expected.width != actual.width || expected.height != actual.height {
+        return Err(format!(
+            "Expected {}x{} image but got {}x{}", expected.width, expected.height, actual.width, actual.height
+        ));
+    }
+
+    let tolerance = input.operation.tolerance();
+    for y in 0..expected.height {
+        for x in 0..expected.width {
+            let (a, b) = (expected.get(x, y), actual.get(x, y));
+            if a.iter().zip(b.iter()).any(|(a, b)| a.abs_diff(*b) > tolerance) {
+                return Err(format!("Pixel ({}, {}) differs: expected {}, found {}", x, y, hex(a), hex(b)));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Runs `<executable> <operation> [args...] <input> <output>` for every test
+pub struct ImageRunner {
+    file: PathBuf,
+}
+
+impl ImageRunner {
+    pub fn new(file: PathBuf) -> Self {
+        ImageRunner { file }
+    }
+
+    fn process(&self, dir: &WorkDir, input: &ImageInput) -> Result<ImageOutput, String> {
+        let source = dir.file(&format!("input.{}", input.format));
+        let target = dir.file(&format!("output.{}", input.format));
+        std::fs::write(&source, codec::encode(&input.image, input.format))
+            .map_err(|a| format!("Cannot write test image: {}", a))?;
+
+        let output = Command::new(&self.file)
+            .args(input.operation.args())
+            .arg(&source)
+            .arg(&target)
+            .output()
+            .map_err(|a| format!("Cannot start process: {}", a))?;
+        if !output.status.success() {
+            return Ok(ImageOutput::Failed(format!(
+                "Process exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(match std::fs::read(&target) {
+            Ok(data) => match codec::decode(&data) {
+                Ok(image) => ImageOutput::Decoded(image),
+                Err(e) => ImageOutput::Failed(format!("Cannot decode output image: {}", e)),
+            },
+            Err(e) => ImageOutput::Failed(format!("Cannot read output image: {}", e)),
+        })
+    }
+}
+
+impl Runner<ImageInput, ImageOutput> for ImageRunner {
+    fn run(&self, input: &[Test<ImageInput, ImageOutput>], listener: &mut Listener<ImageInput, ImageOutput>) -> Result<(), String> {
+        let dir = WorkDir::new()?;
+
+        for test in input {
+            let output = self.process(&dir, &test.input)?;
+            if !listener(test, &output, None) {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn make_test(format: ImageFormat, image: Image, operation: Operation) -> Test<ImageInput, ImageOutput> {
+    let expected = ImageOutput::Decoded(operation.apply(&image));
+    Test::new(ImageInput { format, image, operation }, expected)
+}
+
+fn random_image(rng: &mut dyn RngCore, width: usize, height: usize, gray: bool) -> Image {
+    let mut image = Image::new(width, height);
+    let pattern = rng.gen_range(0..3);
+    let base: [u8; 3] = rng.gen();
+    for y in 0..height {
+        for x in 0..width {
+            let mut pixel = match pattern {
+                0 => rng.gen(),
+                1 => [(x * 255 / width) as u8, (y * 255 / height) as u8, base[2]],
+                _ => if (x + y) % 2 == 0 { base } else { [255 - base[0], 255 - base[1], 255 - base[2]] },
+            };
+            if gray {
+                pixel = [pixel[0]; 3];
+            }
+            image.set(x, y, pixel);
+        }
+    }
+    image
+}
+
+fn random_operation(rng: &mut dyn RngCore, image: &Image) -> Operation {
+    match rng.gen_range(0..4) {
+        0 => {
+            let x = rng.gen_range(0..image.width);
+            let y = rng.gen_range(0..image.height);
+            let width = rng.gen_range(1..=image.width - x);
+            let height = rng.gen_range(1..=image.height - y);
+            Operation::Crop { x, y, width, height }
+        }
+        1 => Operation::Rotate([90, 180, 270][rng.gen_range(0..3)]),
+        2 => Operation::Invert,
+        _ => Operation::Grayscale,
+    }
+}
+
+pub struct ImageSupplier {
+    scale: f64,
+}
+
+impl ImageSupplier {
+    pub fn new(scale: f64) -> ImageSupplier {
+        ImageSupplier { scale }
+    }
+
+    fn hand_made(&self, node: &mut TestsNode<ImageInput, ImageOutput>) {
+        let mut pixel = Image::new(1, 1);
+        pixel.set(0, 0, [10, 200, 30]);
+        let single = node.child("single pixel");
+        for operation in [Operation::Invert, Operation::Grayscale, Operation::Rotate(90), Operation::Crop { x: 0, y: 0, width: 1, height: 1 }] {
+            single.add_test(make_test(ImageFormat::Bmp, pixel.clone(), operation));
+        }
+
+        let mut stripes = Image::new(3, 2);
+        for x in 0..3 {
+            stripes.set(x, 0, [255, 0, 0]);
+            stripes.set(x, 1, [0, 0, 255]);
+        }
+        node.child("row padding")
+            .add_test(make_test(ImageFormat::Bmp, stripes.clone(), Operation::Invert))
+            .add_test(make_test(ImageFormat::Bmp, stripes.clone(), Operation::Rotate(90)))
+            .add_test(make_test(ImageFormat::Bmp, stripes.clone(), Operation::Crop { x: 1, y: 1, width: 2, height: 1 }));
+
+        node.child("rotations")
+            .add_test(make_test(ImageFormat::Ppm, stripes.clone(), Operation::Rotate(90)))
+            .add_test(make_test(ImageFormat::Ppm, stripes.clone(), Operation::Rotate(180)))
+            .add_test(make_test(ImageFormat::Ppm, stripes.clone(), Operation::Rotate(270)));
+
+        node.child("full crop")
+            .add_test(make_test(ImageFormat::Pgm, Image { pixels: vec![[7; 3]; 6], ..stripes }, Operation::Crop { x: 0, y: 0, width: 3, height: 2 }));
+    }
+
+    fn random_group(&self, node: &mut TestsNode<ImageInput, ImageOutput>, rng: &mut dyn RngCore,
+                    format: ImageFormat, num: usize, odd_widths: bool) {
+        for _ in 0..scale_count(num, self.scale) {
+            let mut width = rng.gen_range(1..40);
+            if odd_widths && width % 4 == 0 {
+                width += 1;
+            }
+            let height = rng.gen_range(1..30);
+            let image = random_image(rng, width, height, format == ImageFormat::Pgm);
+            let operation = random_operation(rng, &image);
+            node.add_test(make_test(format, image, operation));
+        }
+    }
+}
+
+impl TestsSupplier<ImageInput, ImageOutput> for ImageSupplier {
+    fn supply_tests(&self, rng: &mut dyn RngCore) -> TestsNode<ImageInput, ImageOutput> {
+        let mut result = TestsNode::new("root");
+
+        self.hand_made(result.child("hand-made"));
+        self.random_group(result.child("bmp odd widths"), rng, ImageFormat::Bmp, 40, true);
+        for format in [ImageFormat::Bmp, ImageFormat::Ppm, ImageFormat::Pgm] {
+            self.random_group(result.child(format!("{} random", format)), rng, format, 40, false);
+        }
+
+        result
+    }
+}
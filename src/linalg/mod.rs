@@ -0,0 +1,311 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use rand::{Rng, RngCore};
+
+use crate::base::runner::{TryFromVerbal, VerbalInput};
+use crate::base::scale::scale_count;
+use crate::base::tests_supply::{Test, TestsNode, TestsSupplier};
+use crate::slae::{Matrix, RowSum, shuffle_matrix};
+
+const TOLERANCE: f64 = 1e-6;
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= TOLERANCE * f64::max(1.0, a.abs())
+}
+
+fn permutation_sign(order: &[i32]) -> f64 {
+    let mut inversions = 0;
+    for i in 0..order.len() {
+        for j in i + 1..order.len() {
+            if order[i] > order[j] {
+                inversions += 1;
+            }
+        }
+    }
+    if inversions % 2 == 0 { 1.0 } else { -1.0 }
+}
+
+fn square(n: i32, values: &[i32]) -> Matrix {
+    let mut matrix = Matrix::new_square(n);
+    for y in 0..n {
+        for x in 0..n {
+            matrix.set_at(y, x, values[(y * n + x) as usize]);
+        }
+    }
+    matrix
+}
+
+#[derive(Clone)]
+pub struct Determinant(f64);
+
+impl PartialEq for Determinant {
+    fn eq(&self, other: &Self) -> bool {
+        close(self.0, other.0)
+    }
+}
+
+impl Eq for Determinant {}
+
+impl TryFromVerbal for Determinant {
+    fn try_from(s: &str) -> Result<Self, String> {
+        f64::from_str(s.trim()).map(Determinant).map_err(|a| a.to_string())
+    }
+}
+
+impl Display for Determinant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Clone)]
+pub struct DeterminantInput(Matrix);
+
+impl Display for DeterminantInput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl VerbalInput for DeterminantInput {
+    fn is_output_complete(&self, output: &[u8]) -> bool {
+        output.ends_with(b"\n")
+    }
+}
+
+#[derive(Clone)]
+pub struct InverseInput(Matrix);
+
+impl Display for InverseInput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl VerbalInput for InverseInput {
+    fn is_output_complete(&self, output: &[u8]) -> bool {
+        output.ends_with(b"\n") && (
+            output.starts_with(b"singular")
+                || output.iter().filter(|a| **a == b'\n').count() >= self.0.size() as usize
+        )
+    }
+}
+
+pub const SINGULAR: &str = "singular matrix";
+
+#[derive(Clone)]
+pub enum Inverse {
+    Singular,
+    Found(Vec<Vec<f64>>),
+}
+
+impl PartialEq for Inverse {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Inverse::Singular, Inverse::Singular) => true,
+            (Inverse::Found(a), Inverse::Found(b)) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| {
+                    a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| close(*a, *b))
+                })
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Inverse {}
+
+impl TryFromVerbal for Inverse {
+    fn try_from(s: &str) -> Result<Self, String> {
+        if s.trim() == SINGULAR {
+            return Ok(Inverse::Singular);
+        }
+        let rows = s.lines()
+            .filter(|a| !a.trim().is_empty())
+            .map(|line| {
+                line.split_whitespace()
+                    .map(|a| f64::from_str(a).map_err(|a| a.to_string()))
+                    .collect::<Result<Vec<f64>, String>>()
+            })
+            .collect::<Result<Vec<Vec<f64>>, String>>()?;
+        Ok(Inverse::Found(rows))
+    }
+}
+
+impl Display for Inverse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Inverse::Singular => f.write_str(SINGULAR),
+            Inverse::Found(rows) => {
+                for row in rows {
+                    for value in row {
+                        write!(f, "{} ", value)?;
+                    }
+                    writeln!(f)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Upper triangular matrix, so the determinant is the product of the diagonal
+fn triangular(rng: &mut dyn RngCore, n: i32, singular: bool) -> (Matrix, f64) {
+    let mut matrix = Matrix::new_square(n);
+    let mut det = 1.0;
+    let zero = if singular { rng.gen_range(0..n) } else { -1 };
+    for y in 0..n {
+        let mut diagonal = 0;
+        while diagonal == 0 && y != zero {
+            diagonal = rng.gen_range(-5..6);
+        }
+        matrix.set_at(y, y, diagonal);
+        det *= diagonal as f64;
+        for x in y + 1..n {
+            matrix.set_at(y, x, rng.gen_range(-10..11));
+        }
+    }
+    (matrix, det)
+}
+
+fn track_determinant(det: f64, sums: &[RowSum], matrix: &Matrix) -> f64 {
+    let scale: f64 = sums.iter()
+        .filter(|a| a.transmitter == a.receiver)
+        .map(|a| (1 + a.factor) as f64)
+        .product();
+    det * scale * permutation_sign(matrix.row_order())
+}
+
+/// Every row operation on the matrix is undone by the opposite column operation on its inverse
+fn track_inverse(mut inverse: Vec<Vec<f64>>, sums: &[RowSum], matrix: &Matrix) -> Vec<Vec<f64>> {
+    for sum in sums {
+        let (a, b) = (sum.transmitter as usize, sum.receiver as usize);
+        for row in inverse.iter_mut() {
+            if a == b {
+                row[a] /= (1 + sum.factor) as f64;
+            } else {
+                row[a] -= sum.factor as f64 * row[b];
+            }
+        }
+    }
+    let order = matrix.row_order();
+    inverse.iter()
+        .map(|row| order.iter().map(|a| row[*a as usize]).collect())
+        .collect()
+}
+
+pub struct DeterminantSupplier {
+    scale: f64,
+}
+
+impl DeterminantSupplier {
+    pub fn new(scale: f64) -> DeterminantSupplier {
+        DeterminantSupplier { scale }
+    }
+
+    fn hand_made(&self, node: &mut TestsNode<DeterminantInput, Determinant>) {
+        node.child("1x1")
+            .add_test(Test::new(DeterminantInput(square(1, &[7])), Determinant(7.0)))
+            .add_test(Test::new(DeterminantInput(square(1, &[0])), Determinant(0.0)));
+        node.child("zero pivot")
+            .add_test(Test::new(DeterminantInput(square(2, &[0, 1, 1, 0])), Determinant(-1.0)))
+            .add_test(Test::new(DeterminantInput(square(3, &[0, 2, 1, 1, 1, 1, 2, 1, 0])), Determinant(3.0)));
+        node.child("singular")
+            .add_test(Test::new(DeterminantInput(square(2, &[1, 2, 2, 4])), Determinant(0.0)))
+            .add_test(Test::new(DeterminantInput(square(3, &[1, 2, 3, 4, 5, 6, 7, 8, 9])), Determinant(0.0)));
+    }
+
+    fn random_group(&self, node: &mut TestsNode<DeterminantInput, Determinant>, rng: &mut dyn RngCore,
+                    num: usize, size: std::ops::Range<i32>, singular: bool) {
+        for _ in 0..scale_count(num, self.scale) {
+            let n = rng.gen_range(size.clone());
+            let (mut matrix, det) = triangular(rng, n, singular);
+            let sums = matrix.size() * 4;
+            let applied = shuffle_matrix(&mut matrix, rng, 4, sums);
+            let det = track_determinant(det, &applied, &matrix);
+            node.add_test(Test::new(DeterminantInput(matrix), Determinant(det)));
+        }
+    }
+}
+
+impl TestsSupplier<DeterminantInput, Determinant> for DeterminantSupplier {
+    fn supply_tests(&self, rng: &mut dyn RngCore) -> TestsNode<DeterminantInput, Determinant> {
+        let mut result = TestsNode::new("root");
+
+        self.hand_made(result.child("hand-made"));
+        self.random_group(result.child("random small"), rng, 100, 1..5, false);
+        self.random_group(result.child("random large"), rng, 30, 5..10, false);
+        self.random_group(result.child("singular"), rng, 50, 2..8, true);
+
+        result
+    }
+}
+
+pub struct InverseSupplier {
+    scale: f64,
+}
+
+impl InverseSupplier {
+    pub fn new(scale: f64) -> InverseSupplier {
+        InverseSupplier { scale }
+    }
+
+    fn hand_made(&self, node: &mut TestsNode<InverseInput, Inverse>) {
+        node.child("1x1")
+            .add_test(Test::new(InverseInput(square(1, &[2])), Inverse::Found(vec![vec![0.5]])))
+            .add_test(Test::new(InverseInput(square(1, &[0])), Inverse::Singular));
+        node.child("2x2")
+            .add_test(Test::new(
+                InverseInput(square(2, &[1, 2, 3, 4])),
+                Inverse::Found(vec![vec![-2.0, 1.0], vec![1.5, -0.5]]),
+            ))
+            .add_test(Test::new(
+                InverseInput(square(2, &[0, 1, 1, 0])),
+                Inverse::Found(vec![vec![0.0, 1.0], vec![1.0, 0.0]]),
+            ));
+        node.child("singular")
+            .add_test(Test::new(InverseInput(square(2, &[1, 2, 2, 4])), Inverse::Singular))
+            .add_test(Test::new(InverseInput(square(3, &[1, 2, 3, 4, 5, 6, 7, 8, 9])), Inverse::Singular));
+    }
+
+    fn random_group(&self, node: &mut TestsNode<InverseInput, Inverse>, rng: &mut dyn RngCore,
+                    num: usize, size: std::ops::Range<i32>, singular: bool) {
+        for _ in 0..scale_count(num, self.scale) {
+            let n = rng.gen_range(size.clone());
+            let mut matrix = Matrix::new_square(n);
+            let mut inverse = vec![vec![0.0; n as usize]; n as usize];
+            let zero = if singular { rng.gen_range(0..n) } else { -1 };
+            for i in 0..n {
+                // Powers of two keep the starting inverse exact
+                let diagonal = if i == zero { 0 } else { [1, -1, 2, -2, 4, -4][rng.gen_range(0..6)] };
+                matrix.set_at(i, i, diagonal);
+                if diagonal != 0 {
+                    inverse[i as usize][i as usize] = 1.0 / diagonal as f64;
+                }
+            }
+
+            let sums = n * 4;
+            let applied = shuffle_matrix(&mut matrix, rng, 4, sums);
+            let expected = if singular {
+                Inverse::Singular
+            } else {
+                Inverse::Found(track_inverse(inverse, &applied, &matrix))
+            };
+            node.add_test(Test::new(InverseInput(matrix), expected));
+        }
+    }
+}
+
+impl TestsSupplier<InverseInput, Inverse> for InverseSupplier {
+    fn supply_tests(&self, rng: &mut dyn RngCore) -> TestsNode<InverseInput, Inverse> {
+        let mut result = TestsNode::new("root");
+
+        self.hand_made(result.child("hand-made"));
+        self.random_group(result.child("random small"), rng, 100, 1..5, false);
+        self.random_group(result.child("random large"), rng, 30, 5..10, false);
+        self.random_group(result.child("singular"), rng, 50, 2..8, true);
+
+        result
+    }
+}
@@ -1,54 +1,316 @@
 use crate::slae::{SLAESupplier, Answer, Matrix};
-use crate::base::test_runner::{TestsRunner, FailedTest};
-use crate::base::runner::BatchStdIORunner;
+use crate::slae::residual;
+use crate::base::external_checker::ExternalChecker;
+use crate::base::format::FormatProfile;
+use crate::base::fuzz;
+use crate::base::interactive::{self, InteractiveRunner};
+use crate::base::config::Config;
+use crate::base::coverage::Coverage;
+use crate::base::scale::{self, RunProfile};
+use crate::slae::config::SLAEConfig;
+use crate::sort::SortSupplier;
+use crate::bigint::BigIntSupplier;
+use crate::calc::CalcSupplier;
+use crate::calendar::CalendarSupplier;
+use crate::graph::GraphSupplier;
+use crate::guess::{GuessInteractor, GuessSupplier};
+use crate::linalg::{DeterminantSupplier, InverseSupplier};
+use crate::archiver::{ArchiverRunner, ArchiverSupplier};
+use crate::image::{ImageRunner, ImageSupplier};
+use crate::numeric::NumericSupplier;
+use crate::poly::PolySupplier;
+use crate::structures::{ScriptChecker, Structure, StructureSupplier};
+use crate::text::TextSupplier;
+use crate::base::test_runner::TestsRunner;
+use crate::base::tests_supply;
+use crate::base::runner::{self, BatchStdIORunner};
 use std::path::PathBuf;
-use clap::Parser;
-use rand::SeedableRng;
+use std::time::Duration;
+use clap::{Parser, Subcommand};
+use rand::RngCore;
+use std::fmt::Display;
 
+mod archiver;
 mod base;
+mod bigint;
+mod calc;
+mod calendar;
+mod graph;
+mod guess;
+mod image;
+mod linalg;
+mod numeric;
+mod poly;
 mod slae;
+mod sort;
+mod structures;
+mod text;
 
 #[derive(Debug, Parser)]
 #[clap(about)]
 struct Cli {
     /// Path to executable you want to test
-    #[clap(parse(from_os_str), long)]
-    executable: PathBuf,
+    #[clap(parse(from_os_str), long, required_unless_present="coverage")]
+    executable: Option<PathBuf>,
 
-    /// Name of lab you want to test. Options: slae
+    /// Name of lab you want to test. Options: slae, sort, bigint, det, inverse, archiver, image, numeric, map, list, heap, text, poly, calendar, path, components, toposort, calc, guess
     #[clap(long)]
     lab: String,
 
     /// Seed to tune random generator
     #[clap(long, default_value="qwerty")]
-    seed: String
+    seed: String,
+
+    /// Check found solutions by substituting them into the system instead of comparing with the expected vector
+    #[clap(long)]
+    residual: bool,
+
+    /// Maximal relative residual accepted when --residual is set
+    #[clap(long, default_value_t=residual::DEFAULT_TOLERANCE)]
+    residual_tolerance: f64,
+
+    /// Output format strictness. Options: lenient, lab-spec, exact-bytes. Defaults to the lab's own profile
+    #[clap(long)]
+    format: Option<FormatProfile>,
+
+    /// Path to SLAE config file with generator parameters. Defaults to the built-in full profile
+    #[clap(parse(from_os_str), long)]
+    config: Option<PathBuf>,
+
+    /// Print the generator config in use and exit
+    #[clap(long)]
+    print_config: bool,
+
+    /// Test count profile. Options: smoke, normal, exhaustive. Hand-made tests are always kept
+    #[clap(long, default_value_t=RunProfile::Normal)]
+    profile: RunProfile,
+
+    /// Multiplier for the number of random tests in every group. Overrides --profile
+    #[clap(long, parse(try_from_str=scale::parse_factor))]
+    scale: Option<f64>,
+
+    /// Run every test instead of stopping at the first failure and print the weighted score
+    #[clap(long)]
+    grade: bool,
+
+    /// Path to a testlib-style checker run as `<checker> <input> <actual> <expected>` instead of the lab's own check
+    #[clap(parse(from_os_str), long)]
+    checker: Option<PathBuf>,
+
+    /// Time limit in milliseconds for every answer in interactive labs
+    #[clap(long, default_value_t=interactive::DEFAULT_ROUND_TIMEOUT.as_millis() as u64)]
+    round_timeout: u64,
+
+    /// Time limit in milliseconds for the complete answer to one test in labs which read stdin
+    #[clap(long, default_value_t=runner::DEFAULT_TEST_TIMEOUT.as_millis() as u64)]
+    test_timeout: u64,
+
+    /// C sources of the lab, repeat for several files. They are built with `gcc --coverage` and tested instead of
+    /// --executable, then the lines never executed are reported. Only `fuzz` runs groups which reach new lines more
+    /// often, a plain run keeps its test set
+    #[clap(parse(from_os_str), long)]
+    coverage: Vec<PathBuf>,
+
+    #[clap(subcommand)]
+    command: Option<Command>
 }
 
-fn main() {
-    let args = Cli::parse();
-    let slae = SLAESupplier::new();
-
-    use sha2::Digest;
-    let mut hasher = sha2::Sha256::new();
-    hasher.update(args.seed);
-    let mut seed = [77;32];
-    &mut seed[..].copy_from_slice(&hasher.finalize()[..]);
-
-    let runner = TestsRunner::new(
-        slae,
-        BatchStdIORunner::new(args.executable)
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Run the random groups with fresh seeds until the time is over or a test fails
+    Fuzz {
+        /// Time budget like 90s, 10m or 1h
+        #[clap(long, parse(try_from_str=fuzz::parse_duration))]
+        duration: Duration,
+
+        /// Directory where a failing test is saved together with its seed
+        #[clap(parse(from_os_str), long, default_value="crashes")]
+        crash_dir: PathBuf,
+    }
+}
+
+fn run_tests<Input, Output>(mut runner: TestsRunner<Input, Output>, args: &Cli, coverage: &mut Option<Coverage>,
+                            rng: &mut dyn RngCore) -> Result<(), String>
+    where Input: Clone + Display, Output: Clone + Display
+{
+    if let Some(checker) = &args.checker {
+        runner = runner.with_checker(ExternalChecker::new(checker.clone())?);
+    }
+    if let Some(coverage) = coverage.take() {
+        runner = runner.with_coverage(coverage);
+    }
+    if let Some(Command::Fuzz { duration, crash_dir }) = &args.command {
+        if let Some(failure) = runner.fuzz(*duration, crash_dir, rng)? {
+            eprintln!("{}", failure);
+        }
+        return Ok(());
+    }
+    let report = runner.with_grading(args.grade).run(rng)?;
+    if let Some(failed) = report.failed {
+        eprintln!("{}", failed);
+    }
+    if !report.skipped.is_empty() {
+        println!("Skipped: {}", report.skipped.join(", "));
+    }
+    if let Some(score) = report.score {
+        println!("Score: {:.2}%", score * 100.0);
+    }
+    Ok(())
+}
+
+fn executable(args: &Cli) -> PathBuf {
+    args.executable.clone().expect("executable is set unless testing for coverage")
+}
+
+fn batch_runner(args: &Cli, default_format: FormatProfile) -> BatchStdIORunner {
+    let runner = BatchStdIORunner::new(executable(args))
+        .with_format(args.format.unwrap_or(default_format))
+        .with_timeout(Duration::from_millis(args.test_timeout));
+    // Coverage counters are written only when the program exits on its own
+    if args.coverage.is_empty() {
+        runner
+    } else {
+        runner.with_exit_grace(runner::DEFAULT_EXIT_GRACE)
+    }
+}
+
+fn run_slae(args: &Cli, scale: f64, coverage: &mut Option<Coverage>, rng: &mut dyn RngCore) -> Result<(), String> {
+    let config = match &args.config {
+        Some(path) => SLAEConfig::from_config(&Config::load(path)?)?,
+        None => SLAEConfig::default(),
+    };
+    let config = config.scaled(scale);
+    if args.print_config {
+        print!("{}", config);
+        return Ok(());
+    }
+
+    let mut runner = TestsRunner::new(
+        SLAESupplier::new(config),
+        batch_runner(args, slae::format::DEFAULT_PROFILE)
     );
 
-    let mut rng = rand::rngs::SmallRng::from_seed(seed);
-    match runner.run(&mut rng) {
-        Ok(failed) => {
-            if let Some(failed) = failed {
-                eprintln!("{}", failed);
+    if args.residual {
+        let tolerance = args.residual_tolerance;
+        runner = runner.with_checker(move |matrix: &Matrix, expected: &Answer, actual: &Answer| {
+            residual::check_residual(matrix, expected, actual, tolerance)
+        });
+    }
+
+    run_tests(runner, args, coverage, rng)
+}
+
+fn main() {
+    let mut args = Cli::parse();
+
+    let mut rng = tests_supply::seeded_rng(&args.seed);
+    let scale = args.scale.unwrap_or_else(|| args.profile.factor());
+    let mut coverage = None;
+    if !args.coverage.is_empty() {
+        match Coverage::build(&args.coverage) {
+            Ok(built) => {
+                args.executable = Some(built.executable().to_path_buf());
+                coverage = Some(built);
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
             }
-        }
-        Err(e) => {
-            eprintln!("{}", e)
         }
     }
 
+    let result = match args.lab.as_str() {
+        "slae" => run_slae(&args, scale, &mut coverage, &mut rng),
+        "sort" => run_tests(
+            TestsRunner::new(SortSupplier::new(scale), batch_runner(&args, FormatProfile::Lenient))
+                .with_checker(sort::check),
+            &args, &mut coverage, &mut rng
+        ),
+        "bigint" => run_tests(
+            TestsRunner::new(BigIntSupplier::new(scale), batch_runner(&args, FormatProfile::Lenient)),
+            &args, &mut coverage, &mut rng
+        ),
+        "det" => run_tests(
+            TestsRunner::new(DeterminantSupplier::new(scale), batch_runner(&args, FormatProfile::Lenient)),
+            &args, &mut coverage, &mut rng
+        ),
+        "inverse" => run_tests(
+            TestsRunner::new(InverseSupplier::new(scale), batch_runner(&args, FormatProfile::Lenient)),
+            &args, &mut coverage, &mut rng
+        ),
+        "archiver" => run_tests(
+            TestsRunner::new(ArchiverSupplier::new(scale), ArchiverRunner::new(executable(&args)))
+                .with_checker(archiver::check)
+                .with_chunk_note(archiver::ratio_note),
+            &args, &mut coverage, &mut rng
+        ),
+        "image" => run_tests(
+            TestsRunner::new(ImageSupplier::new(scale), ImageRunner::new(executable(&args)))
+                .with_checker(image::check),
+            &args, &mut coverage, &mut rng
+        ),
+        "numeric" => run_tests(
+            TestsRunner::new(NumericSupplier::new(scale), batch_runner(&args, FormatProfile::Lenient))
+                .with_checker(numeric::check),
+            &args, &mut coverage, &mut rng
+        ),
+        "map" | "list" | "heap" => {
+            let structure = match args.lab.as_str() {
+                "map" => Structure::Map,
+                "list" => Structure::List,
+                _ => Structure::Heap,
+            };
+            run_tests(
+                TestsRunner::new(StructureSupplier::new(structure, scale), batch_runner(&args, FormatProfile::Lenient))
+                    .with_checker(ScriptChecker),
+                &args, &mut coverage, &mut rng
+            )
+        }
+        "text" => run_tests(
+            TestsRunner::new(TextSupplier::new(scale), batch_runner(&args, FormatProfile::Lenient))
+                .with_checker(text::check),
+            &args, &mut coverage, &mut rng
+        ),
+        "poly" => run_tests(
+            TestsRunner::new(PolySupplier::new(scale), batch_runner(&args, FormatProfile::Lenient)),
+            &args, &mut coverage, &mut rng
+        ),
+        "calendar" => run_tests(
+            TestsRunner::new(CalendarSupplier::new(scale), batch_runner(&args, FormatProfile::Lenient)),
+            &args, &mut coverage, &mut rng
+        ),
+        "calc" => run_tests(
+            TestsRunner::new(CalcSupplier::new(scale), batch_runner(&args, FormatProfile::Lenient)),
+            &args, &mut coverage, &mut rng
+        ),
+        "guess" => run_tests(
+            TestsRunner::new(
+                GuessSupplier::new(scale),
+                InteractiveRunner::new(executable(&args), GuessInteractor)
+                    .with_timeout(Duration::from_millis(args.round_timeout))
+            ),
+            &args, &mut coverage, &mut rng
+        ),
+        "path" | "components" | "toposort" => {
+            let task = match args.lab.as_str() {
+                "path" => graph::Task::ShortestPath,
+                "components" => graph::Task::Components,
+                _ => graph::Task::TopologicalSort,
+            };
+            run_tests(
+                TestsRunner::new(GraphSupplier::new(task, scale), batch_runner(&args, FormatProfile::Lenient))
+                    .with_checker(graph::check),
+                &args, &mut coverage, &mut rng
+            )
+        }
+        lab => Err(format!(
+            "Unknown lab '{}'. Options: slae, sort, bigint, det, inverse, archiver, image, numeric, map, list, heap, \
+            text, poly, calendar, path, components, toposort, calc, guess",
+            lab
+        )),
+    };
+
+    if let Err(e) = result {
+        eprintln!("{}", e)
+    }
 }
@@ -0,0 +1,242 @@
+use std::f64::consts::PI;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use rand::{Rng, RngCore};
+use rand::seq::SliceRandom;
+
+use crate::base::runner::{TryFromVerbal, VerbalInput};
+use crate::base::scale::scale_count;
+use crate::base::tests_supply::{Test, TestsNode, TestsSupplier};
+
+#[derive(Clone, PartialEq)]
+pub enum Function {
+    /// Coefficients from the constant term up
+    Polynomial(Vec<f64>),
+    /// `a * sin(b * x + c) + d`
+    Trigonometric { a: f64, b: f64, c: f64, d: f64 },
+}
+
+impl Function {
+    fn from_roots(leading: f64, roots: &[f64]) -> Function {
+        let mut coefficients = vec![leading];
+        for root in roots {
+            let mut next = vec![0.0; coefficients.len() + 1];
+            for (i, c) in coefficients.iter().enumerate() {
+                next[i + 1] += c;
+                next[i] -= c * root;
+            }
+            coefficients = next;
+        }
+        Function::Polynomial(coefficients)
+    }
+
+    fn antiderivative(&self, x: f64) -> f64 {
+        match self {
+            Function::Polynomial(coefficients) => coefficients.iter()
+                .enumerate()
+                .map(|(i, c)| c * x.powi(i as i32 + 1) / (i + 1) as f64)
+                .sum(),
+            Function::Trigonometric { a, b, c, d } => -a / b * (b * x + c).cos() + d * x,
+        }
+    }
+}
+
+impl Display for Function {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Function::Polynomial(coefficients) => {
+                write!(f, "poly {}", coefficients.len() - 1)?;
+                for c in coefficients {
+                    write!(f, " {}", c)?;
+                }
+                Ok(())
+            }
+            Function::Trigonometric { a, b, c, d } => write!(f, "trig {} {} {} {}", a, b, c, d),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Task {
+    Root,
+    Integral,
+}
+
+#[derive(Clone)]
+pub struct NumericInput {
+    task: Task,
+    from: f64,
+    to: f64,
+    precision: f64,
+    function: Function,
+}
+
+impl Display for NumericInput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let task = match self.task {
+            Task::Root => "root",
+            Task::Integral => "integral",
+        };
+        writeln!(f, "{} {} {} {:e}", task, self.from, self.to, self.precision)?;
+        writeln!(f, "{}", self.function)
+    }
+}
+
+impl VerbalInput for NumericInput {
+    fn is_output_complete(&self, output: &[u8]) -> bool {
+        output.ends_with(b"\n")
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Approximation(f64);
+
+impl Eq for Approximation {}
+
+impl TryFromVerbal for Approximation {
+    fn try_from(s: &str) -> Result<Self, String> {
+        f64::from_str(s.trim()).map(Approximation).map_err(|a| a.to_string())
+    }
+}
+
+impl Display for Approximation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Tolerance comes from the precision requested in the test itself
+pub fn check(input: &NumericInput, expected: &Approximation, actual: &Approximation) -> Result<(), String> {
+    let error = (expected.0 - actual.0).abs();
+    if error.is_nan() || error > input.precision {
+        return Err(format!("Error {:e} exceeds requested precision {:e}", error, input.precision));
+    }
+    Ok(())
+}
+
+const PRECISIONS: [f64; 4] = [1e-3, 1e-4, 1e-5, 1e-6];
+
+fn make_test(task: Task, from: f64, to: f64, precision: f64, function: Function, answer: f64) -> Test<NumericInput, Approximation> {
+    Test::new(NumericInput { task, from, to, precision, function }, Approximation(answer))
+}
+
+fn integral(from: f64, to: f64, precision: f64, function: Function) -> Test<NumericInput, Approximation> {
+    let answer = function.antiderivative(to) - function.antiderivative(from);
+    make_test(Task::Integral, from, to, precision, function, answer)
+}
+
+/// Interval strictly around `root` which stays away from all the other roots
+fn isolate(rng: &mut dyn RngCore, root: f64, others: &[f64]) -> (f64, f64) {
+    let gap = others.iter()
+        .map(|a| (a - root).abs())
+        .filter(|a| *a > 0.0)
+        .fold(4.0, f64::min);
+    let left = (rng.gen_range(0.1..0.9) * gap * 8.0).round() / 16.0;
+    let right = (rng.gen_range(0.1..0.9) * gap * 8.0).round() / 16.0;
+    (root - left.max(1.0 / 16.0), root + right.max(1.0 / 16.0))
+}
+
+fn round2(x: f64) -> f64 {
+    (x * 100.0).round() / 100.0
+}
+
+fn random_trigonometric(rng: &mut dyn RngCore) -> (f64, f64, f64, f64) {
+    let a = round2(rng.gen_range(0.5..5.0)) * if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+    let b = round2(rng.gen_range(0.5..3.0));
+    let c = round2(rng.gen_range(-3.0..3.0));
+    // |d| < |a| keeps every root simple, so the sign changes on it
+    let d = round2(rng.gen_range(-0.8..0.8) * a.abs());
+    (a, b, c, d)
+}
+
+pub struct NumericSupplier {
+    scale: f64,
+}
+
+impl NumericSupplier {
+    pub fn new(scale: f64) -> NumericSupplier {
+        NumericSupplier { scale }
+    }
+
+    fn hand_made(&self, node: &mut TestsNode<NumericInput, Approximation>) {
+        node.child("roots")
+            .add_test(make_test(Task::Root, 0.0, 2.0, 1e-6, Function::Polynomial(vec![-1.0, 1.0]), 1.0))
+            .add_test(make_test(Task::Root, 0.0, 2.0, 1e-6, Function::Polynomial(vec![-2.0, 0.0, 1.0]), 2f64.sqrt()))
+            .add_test(make_test(Task::Root, 3.0, 4.0, 1e-6, Function::Trigonometric { a: 1.0, b: 1.0, c: 0.0, d: 0.0 }, PI))
+            .add_test(make_test(Task::Root, -1.0, 1.0, 1e-4, Function::Polynomial(vec![0.0, 1.0, 0.0, 1.0]), 0.0));
+        node.child("integrals")
+            .add_test(integral(0.0, 1.0, 1e-6, Function::Polynomial(vec![1.0])))
+            .add_test(integral(0.0, 3.0, 1e-6, Function::Polynomial(vec![0.0, 0.0, 1.0])))
+            .add_test(integral(-2.0, 2.0, 1e-6, Function::Polynomial(vec![0.0, 1.0, 0.0, 1.0])))
+            .add_test(integral(0.0, 3.0, 1e-5, Function::Trigonometric { a: 1.0, b: 1.0, c: 0.0, d: 0.0 }))
+            .add_test(integral(1.0, 1.0, 1e-6, Function::Polynomial(vec![5.0, 1.0])));
+    }
+
+    fn polynomial_roots(&self, node: &mut TestsNode<NumericInput, Approximation>, rng: &mut dyn RngCore, num: usize, precisions: &[f64]) {
+        for _ in 0..scale_count(num, self.scale) {
+            // Halves keep every coefficient exact in binary
+            let roots: Vec<f64> = (0..rng.gen_range(1..5)).map(|_| rng.gen_range(-10..=10) as f64 / 2.0).collect();
+            // Only simple roots change the sign
+            let simple: Vec<f64> = roots.iter()
+                .filter(|a| roots.iter().filter(|b| b == a).count() == 1)
+                .cloned()
+                .collect();
+            let root = match simple.choose(rng) {
+                Some(root) => *root,
+                None => roots[0],
+            };
+            let roots = if simple.is_empty() { vec![root] } else { roots };
+            let (from, to) = isolate(rng, root, &roots);
+            let function = Function::from_roots(rng.gen_range(1..4) as f64, &roots);
+            node.add_test(make_test(Task::Root, from, to, *precisions.choose(rng).unwrap(), function, root));
+        }
+    }
+
+    fn trigonometric_roots(&self, node: &mut TestsNode<NumericInput, Approximation>, rng: &mut dyn RngCore, num: usize, precisions: &[f64]) {
+        for _ in 0..scale_count(num, self.scale) {
+            let (a, b, c, d) = random_trigonometric(rng);
+            let base = (-d / a).asin();
+            let k = rng.gen_range(-2..=2) as f64;
+            let roots: Vec<f64> = [base, PI - base].iter()
+                .flat_map(|t| (-1..=1).map(move |j| ((t + 2.0 * PI * (k + j as f64)) - c) / b))
+                .collect();
+            let root = (base + 2.0 * PI * k - c) / b;
+            let (from, to) = isolate(rng, root, &roots);
+            let function = Function::Trigonometric { a, b, c, d };
+            node.add_test(make_test(Task::Root, from, to, *precisions.choose(rng).unwrap(), function, root));
+        }
+    }
+
+    fn integrals(&self, node: &mut TestsNode<NumericInput, Approximation>, rng: &mut dyn RngCore, num: usize, precisions: &[f64]) {
+        for _ in 0..scale_count(num, self.scale) {
+            let from = rng.gen_range(-40..40) as f64 / 4.0;
+            let to = from + rng.gen_range(1..40) as f64 / 4.0;
+            let function = if rng.gen_bool(0.5) {
+                Function::Polynomial((0..rng.gen_range(1..6)).map(|_| rng.gen_range(-8..=8) as f64 / 4.0).collect())
+            } else {
+                let (a, b, c, d) = random_trigonometric(rng);
+                Function::Trigonometric { a, b, c, d }
+            };
+            node.add_test(integral(from, to, *precisions.choose(rng).unwrap(), function));
+        }
+    }
+}
+
+impl TestsSupplier<NumericInput, Approximation> for NumericSupplier {
+    fn supply_tests(&self, rng: &mut dyn RngCore) -> TestsNode<NumericInput, Approximation> {
+        let mut result = TestsNode::new("root");
+
+        self.hand_made(result.child("hand-made"));
+        self.polynomial_roots(result.child("polynomial roots"), rng, 60, &PRECISIONS);
+        self.trigonometric_roots(result.child("trigonometric roots"), rng, 60, &PRECISIONS);
+        self.integrals(result.child("integrals"), rng, 60, &PRECISIONS);
+
+        let precise = result.child("high precision");
+        self.polynomial_roots(precise, rng, 20, &[1e-9]);
+        self.trigonometric_roots(precise, rng, 20, &[1e-9]);
+        self.integrals(precise, rng, 20, &[1e-8]);
+
+        result
+    }
+}
@@ -0,0 +1,213 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use rand::{Rng, RngCore};
+
+use crate::base::runner::{TryFromVerbal, VerbalInput};
+use crate::base::scale::scale_count;
+use crate::base::tests_supply::{Test, TestsNode, TestsSupplier};
+use crate::poly::polynomial::{Polynomial, Rational};
+
+pub mod polynomial;
+
+const OPERATIONS: [Operation; 5] = [
+    Operation::Add, Operation::Mul, Operation::DivRem, Operation::Evaluate, Operation::Derivative,
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Add,
+    Mul,
+    /// Quotient and remainder on separate lines
+    DivRem,
+    /// Second operand is the point
+    Evaluate,
+    Derivative,
+}
+
+impl Operation {
+    fn name(&self) -> &'static str {
+        match self {
+            Operation::Add => "add",
+            Operation::Mul => "mul",
+            Operation::DivRem => "divmod",
+            Operation::Evaluate => "eval",
+            Operation::Derivative => "diff",
+        }
+    }
+
+    fn operands(&self) -> usize {
+        if *self == Operation::Derivative { 1 } else { 2 }
+    }
+
+    fn answer_lines(&self) -> usize {
+        if *self == Operation::DivRem { 2 } else { 1 }
+    }
+}
+
+/// Operands are kept as text, so hand-made tests can feed non-canonical forms like `0x^3 + x`
+#[derive(Clone)]
+pub struct PolyInput {
+    operation: Operation,
+    operands: Vec<String>,
+}
+
+impl Display for PolyInput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.operation.name())?;
+        for operand in &self.operands {
+            writeln!(f, "{}", operand)?;
+        }
+        Ok(())
+    }
+}
+
+impl VerbalInput for PolyInput {
+    fn is_output_complete(&self, output: &[u8]) -> bool {
+        output.iter().filter(|a| **a == b'\n').count() >= self.operation.answer_lines()
+    }
+}
+
+/// Every line is parsed into canonical form, so any spacing and term order is accepted
+#[derive(Clone, PartialEq, Eq)]
+pub struct PolyAnswer {
+    values: Vec<Polynomial>,
+}
+
+impl TryFromVerbal for PolyAnswer {
+    fn try_from(s: &str) -> Result<Self, String> {
+        let values = s.lines()
+            .filter(|a| !a.trim().is_empty())
+            .map(Polynomial::from_str)
+            .collect::<Result<Vec<Polynomial>, String>>()?;
+        Ok(PolyAnswer { values })
+    }
+}
+
+impl Display for PolyAnswer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for value in &self.values {
+            writeln!(f, "{}", value)?;
+        }
+        Ok(())
+    }
+}
+
+fn make_test(operation: Operation, operands: &[&str]) -> Test<PolyInput, PolyAnswer> {
+    let parsed: Vec<Polynomial> = operands.iter().map(|a| Polynomial::from_str(a).unwrap()).collect();
+    let values = match operation {
+        Operation::Add => vec![parsed[0].add(&parsed[1])],
+        Operation::Mul => vec![parsed[0].mul(&parsed[1])],
+        Operation::DivRem => {
+            let (quotient, remainder) = parsed[0].div_rem(&parsed[1]).expect("Zero divisor in test");
+            vec![quotient, remainder]
+        }
+        Operation::Evaluate => vec![Polynomial::constant(parsed[0].evaluate(&parsed[1].coefficient(0)))],
+        Operation::Derivative => vec![parsed[0].derivative()],
+    };
+    let input = PolyInput { operation, operands: operands.iter().map(|a| a.to_string()).collect() };
+    Test::new(input, PolyAnswer { values })
+}
+
+fn random_coefficient(rng: &mut dyn RngCore, range: i64, rational: bool) -> Rational {
+    let num = rng.gen_range(-range..=range);
+    let den = if rational && rng.gen_bool(0.5) { rng.gen_range(2..=6) } else { 1 };
+    Rational::new(num as i128, den)
+}
+
+fn random_polynomial(rng: &mut dyn RngCore, max_degree: usize, range: i64, rational: bool) -> Polynomial {
+    let degree = rng.gen_range(0..=max_degree);
+    let mut coefficients: Vec<Rational> = (0..degree).map(|_| random_coefficient(rng, range, rational)).collect();
+    let mut lead = Rational::ZERO;
+    while lead.is_zero() {
+        lead = random_coefficient(rng, range, rational);
+    }
+    coefficients.push(lead);
+    Polynomial::new(coefficients)
+}
+
+#[derive(Clone, Copy)]
+struct Shape {
+    max_degree: usize,
+    range: i64,
+    rational: bool,
+}
+
+pub struct PolySupplier {
+    scale: f64,
+}
+
+impl PolySupplier {
+    pub fn new(scale: f64) -> PolySupplier {
+        PolySupplier { scale }
+    }
+
+    fn hand_made(&self, node: &mut TestsNode<PolyInput, PolyAnswer>) {
+        node.child("zero polynomial")
+            .add_test(make_test(Operation::Add, &["0", "0"]))
+            .add_test(make_test(Operation::Add, &["x^2 - 1", "-x^2 + 1"]))
+            .add_test(make_test(Operation::Mul, &["0", "x^3 + 2"]))
+            .add_test(make_test(Operation::DivRem, &["0", "x + 1"]))
+            .add_test(make_test(Operation::Evaluate, &["0", "5"]))
+            .add_test(make_test(Operation::Derivative, &["7"]));
+        node.child("leading zeros")
+            .add_test(make_test(Operation::Add, &["0x^3 + 2x + 1", "x"]))
+            .add_test(make_test(Operation::Mul, &["0x^5 + x", "0x^2 + 3"]))
+            .add_test(make_test(Operation::DivRem, &["x^2 + 1", "0x^4 + x + 1"]))
+            .add_test(make_test(Operation::Derivative, &["0x^2 + 0x + 5"]));
+        node.child("constants")
+            .add_test(make_test(Operation::Mul, &["-3", "1/2"]))
+            .add_test(make_test(Operation::DivRem, &["x^2 + 1", "2"]))
+            .add_test(make_test(Operation::DivRem, &["4", "x"]));
+        node.child("division")
+            .add_test(make_test(Operation::DivRem, &["x^3 - 1", "x - 1"]))
+            .add_test(make_test(Operation::DivRem, &["x", "x^2"]))
+            .add_test(make_test(Operation::DivRem, &["x^2 + x + 1", "2x + 1"]));
+        node.child("rational point")
+            .add_test(make_test(Operation::Evaluate, &["x^2 + x", "-1/2"]))
+            .add_test(make_test(Operation::Evaluate, &["1/3x^3 - 1/2", "3"]));
+    }
+
+    fn random_group(&self, node: &mut TestsNode<PolyInput, PolyAnswer>, rng: &mut dyn RngCore,
+                    operation: Operation, num: usize, shape: Shape) {
+        for _ in 0..scale_count(num, self.scale) {
+            let a = random_polynomial(rng, shape.max_degree, shape.range, shape.rational);
+            let b = match operation {
+                Operation::DivRem => random_polynomial(rng, a.degree().clamp(1, 3), shape.range, shape.rational),
+                Operation::Evaluate => Polynomial::constant(random_coefficient(rng, 5, shape.rational)),
+                _ => random_polynomial(rng, shape.max_degree, shape.range, shape.rational),
+            };
+            let operands = [a.to_string(), b.to_string()];
+            let operands: Vec<&str> = operands.iter().take(operation.operands()).map(|a| a.as_str()).collect();
+            node.add_test(make_test(operation, &operands));
+        }
+    }
+}
+
+impl TestsSupplier<PolyInput, PolyAnswer> for PolySupplier {
+    fn supply_tests(&self, rng: &mut dyn RngCore) -> TestsNode<PolyInput, PolyAnswer> {
+        let mut result = TestsNode::new("root");
+
+        self.hand_made(result.child("hand-made"));
+
+        let integer = result.child("integer coefficients");
+        for operation in OPERATIONS.iter() {
+            self.random_group(integer, rng, *operation, 30, Shape { max_degree: 6, range: 9, rational: false });
+        }
+        let rational = result.child("rational coefficients");
+        for operation in OPERATIONS.iter() {
+            self.random_group(rational, rng, *operation, 30, Shape { max_degree: 5, range: 9, rational: true });
+        }
+        let cancellation = result.child("cancellation");
+        for _ in 0..scale_count(20, self.scale) {
+            let a = random_polynomial(rng, 6, 9, true);
+            let b = a.neg().add(&random_polynomial(rng, 1, 9, true));
+            cancellation.add_test(make_test(Operation::Add, &[&a.to_string(), &b.to_string()]));
+        }
+        self.random_group(
+            result.child("high degree"), rng, Operation::Mul, 10, Shape { max_degree: 60, range: 100, rational: false }
+        );
+
+        result
+    }
+}
@@ -0,0 +1,348 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Reduced fraction with a positive denominator. Generated tests are small enough for `i128`,
+/// student output is parsed with the checked operations
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 { a.abs() } else { gcd(b, a % b) }
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational { num: 0, den: 1 };
+    pub const ONE: Rational = Rational { num: 1, den: 1 };
+
+    pub fn new(num: i128, den: i128) -> Rational {
+        assert_ne!(den, 0, "Zero denominator");
+        let g = gcd(num, den) * den.signum();
+        Rational { num: num / g, den: den / g }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    pub fn abs(&self) -> Rational {
+        Rational { num: self.num.abs(), den: self.den }
+    }
+
+    pub fn neg(&self) -> Rational {
+        Rational { num: -self.num, den: self.den }
+    }
+
+    /// `None` on overflow. `i128::MIN` is refused too, so `neg` and `abs` of any value are safe
+    fn checked_new(num: i128, den: i128) -> Option<Rational> {
+        if num == i128::MIN { None } else { Some(Rational::new(num, den)) }
+    }
+
+    pub fn checked_add(&self, other: &Rational) -> Option<Rational> {
+        let num = self.num.checked_mul(other.den)?.checked_add(other.num.checked_mul(self.den)?)?;
+        Rational::checked_new(num, self.den.checked_mul(other.den)?)
+    }
+
+    pub fn checked_mul(&self, other: &Rational) -> Option<Rational> {
+        Rational::checked_new(self.num.checked_mul(other.num)?, self.den.checked_mul(other.den)?)
+    }
+
+    pub fn add(&self, other: &Rational) -> Rational {
+        self.checked_add(other).expect("Rational overflow")
+    }
+
+    pub fn sub(&self, other: &Rational) -> Rational {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &Rational) -> Rational {
+        self.checked_mul(other).expect("Rational overflow")
+    }
+
+    pub fn div(&self, other: &Rational) -> Rational {
+        self.mul(&Rational::new(other.den, other.num))
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(a: i64) -> Self {
+        Rational { num: a as i128, den: 1 }
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if let (Some(a), Some(b)) = (self.num.checked_mul(other.den), other.num.checked_mul(self.den)) {
+            return a.cmp(&b);
+        }
+        // Too large to cross-multiply: integer parts first, then fractional parts `r/d` below 1,
+        // which compare in the opposite order to `d/r`. Denominators shrink like in Euclid's algorithm
+        let (a, b) = (self.num.div_euclid(self.den), other.num.div_euclid(other.den));
+        if a != b {
+            return a.cmp(&b);
+        }
+        match (self.num.rem_euclid(self.den), other.num.rem_euclid(other.den)) {
+            (0, 0) => Ordering::Equal,
+            (0, _) => Ordering::Less,
+            (_, 0) => Ordering::Greater,
+            (r, q) => Rational { num: other.den, den: q }.cmp(&Rational { num: self.den, den: r }),
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+/// Coefficients from the constant term up, without trailing zeros, so equal polynomials are equal vectors
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Polynomial {
+    coefficients: Vec<Rational>,
+}
+
+/// Protects against `x^1000000000` in student output
+const MAX_DEGREE: usize = 10_000;
+
+impl Polynomial {
+    pub fn new(mut coefficients: Vec<Rational>) -> Polynomial {
+        while coefficients.last().is_some_and(|a| a.is_zero()) {
+            coefficients.pop();
+        }
+        Polynomial { coefficients }
+    }
+
+    pub fn zero() -> Polynomial {
+        Polynomial { coefficients: Vec::new() }
+    }
+
+    pub fn constant(value: Rational) -> Polynomial {
+        Polynomial::new(vec![value])
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    /// Degree of the zero polynomial is reported as 0
+    pub fn degree(&self) -> usize {
+        self.coefficients.len().saturating_sub(1)
+    }
+
+    pub fn coefficient(&self, degree: usize) -> Rational {
+        self.coefficients.get(degree).copied().unwrap_or(Rational::ZERO)
+    }
+
+    pub fn neg(&self) -> Polynomial {
+        Polynomial::new(self.coefficients.iter().map(|a| a.neg()).collect())
+    }
+
+    pub fn add(&self, other: &Polynomial) -> Polynomial {
+        let len = self.coefficients.len().max(other.coefficients.len());
+        Polynomial::new((0..len).map(|i| self.coefficient(i).add(&other.coefficient(i))).collect())
+    }
+
+    pub fn mul(&self, other: &Polynomial) -> Polynomial {
+        if self.is_zero() || other.is_zero() {
+            return Polynomial::zero();
+        }
+        let mut result = vec![Rational::ZERO; self.coefficients.len() + other.coefficients.len() - 1];
+        for (i, a) in self.coefficients.iter().enumerate() {
+            for (j, b) in other.coefficients.iter().enumerate() {
+                result[i + j] = result[i + j].add(&a.mul(b));
+            }
+        }
+        Polynomial::new(result)
+    }
+
+    /// Long division, `None` for the zero divisor
+    pub fn div_rem(&self, other: &Polynomial) -> Option<(Polynomial, Polynomial)> {
+        if other.is_zero() {
+            return None;
+        }
+        let lead = other.coefficient(other.degree());
+        let mut remainder = self.coefficients.clone();
+        let mut quotient = vec![Rational::ZERO; self.coefficients.len().saturating_sub(other.degree())];
+        for shift in (0..quotient.len()).rev() {
+            let factor = remainder[shift + other.degree()].div(&lead);
+            quotient[shift] = factor;
+            for (i, a) in other.coefficients.iter().enumerate() {
+                remainder[shift + i] = remainder[shift + i].sub(&factor.mul(a));
+            }
+        }
+        Some((Polynomial::new(quotient), Polynomial::new(remainder)))
+    }
+
+    pub fn evaluate(&self, x: &Rational) -> Rational {
+        self.coefficients.iter().rev().fold(Rational::ZERO, |acc, a| acc.mul(x).add(a))
+    }
+
+    pub fn derivative(&self) -> Polynomial {
+        Polynomial::new(
+            self.coefficients.iter().enumerate().skip(1)
+                .map(|(i, a)| a.mul(&Rational::from(i as i64)))
+                .collect()
+        )
+    }
+}
+
+fn write_term(f: &mut Formatter<'_>, coefficient: &Rational, degree: usize) -> std::fmt::Result {
+    if degree == 0 || *coefficient != Rational::ONE {
+        write!(f, "{}", coefficient)?;
+    }
+    match degree {
+        0 => Ok(()),
+        1 => f.write_str("x"),
+        _ => write!(f, "x^{}", degree),
+    }
+}
+
+/// Highest degree first: `3/2x^3 - x + 1`, and `0` for the zero polynomial
+impl Display for Polynomial {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return f.write_str("0");
+        }
+        let mut first = true;
+        for (degree, coefficient) in self.coefficients.iter().enumerate().rev() {
+            if coefficient.is_zero() {
+                continue;
+            }
+            let negative = *coefficient < Rational::ZERO;
+            match (first, negative) {
+                (true, true) => f.write_str("-")?,
+                (true, false) => {}
+                (false, true) => f.write_str(" - ")?,
+                (false, false) => f.write_str(" + ")?,
+            }
+            write_term(f, &coefficient.abs(), degree)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
+
+struct Cursor<'a> {
+    chars: &'a [char],
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn number(&mut self) -> Result<Option<i128>, String> {
+        let start = self.position;
+        while self.peek().is_some_and(|a| a.is_ascii_digit()) {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Ok(None);
+        }
+        let digits: String = self.chars[start..self.position].iter().collect();
+        digits.parse().map(Some).map_err(|_| format!("Number {} is too large", digits))
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("{} at position {}", message, self.position + 1)
+    }
+}
+
+/// Accepts terms in any order with repeats, optional spaces, `*`, `+ -` and `^1`/`^0`: `2x^2 + 0x + 1` equals `1+2*x^2`
+impl FromStr for Polynomial {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.chars().filter(|a| !a.is_whitespace()).collect();
+        if chars.is_empty() {
+            return Err("Empty polynomial".to_string());
+        }
+        let mut cursor = Cursor { chars: &chars, position: 0 };
+        let mut coefficients = Vec::new();
+
+        while cursor.peek().is_some() {
+            let negative = if cursor.eat('-') {
+                true
+            } else if cursor.eat('+') || cursor.position == 0 {
+                // `+ -3x` is common in student output
+                cursor.eat('-')
+            } else {
+                return Err(cursor.error("Expected '+' or '-'"));
+            };
+
+            let mut coefficient = match cursor.number()? {
+                Some(num) => {
+                    let den = if cursor.eat('/') {
+                        match cursor.number()? {
+                            Some(0) => return Err(cursor.error("Zero denominator")),
+                            Some(den) => den,
+                            None => return Err(cursor.error("Expected denominator")),
+                        }
+                    } else {
+                        1
+                    };
+                    if cursor.eat('*') && cursor.peek() != Some('x') {
+                        return Err(cursor.error("Expected 'x' after '*'"));
+                    }
+                    Some(Rational::new(num, den))
+                }
+                None => None,
+            };
+
+            let degree = if cursor.eat('x') {
+                if cursor.eat('^') {
+                    match cursor.number()? {
+                        Some(degree) if degree <= MAX_DEGREE as i128 => degree as usize,
+                        Some(_) => return Err(cursor.error("Degree is too large")),
+                        None => return Err(cursor.error("Expected degree")),
+                    }
+                } else {
+                    1
+                }
+            } else if coefficient.is_none() {
+                return Err(cursor.error("Expected coefficient or 'x'"));
+            } else {
+                0
+            };
+
+            if negative {
+                coefficient = Some(coefficient.unwrap_or(Rational::ONE).neg());
+            }
+            if coefficients.len() <= degree {
+                coefficients.resize(degree + 1, Rational::ZERO);
+            }
+            coefficients[degree] = coefficients[degree].checked_add(&coefficient.unwrap_or(Rational::ONE))
+                .ok_or_else(|| cursor.error("Coefficient is too large"))?;
+        }
+
+        Ok(Polynomial::new(coefficients))
+    }
+}
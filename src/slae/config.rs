@@ -0,0 +1,190 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::base::config::{Config, Section};
+use crate::base::scale::scale_count;
+use crate::base::tests_supply::Scoring;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GroupKind {
+    One,
+    None,
+    Many,
+}
+
+impl FromStr for GroupKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "one" => Ok(GroupKind::One),
+            "none" => Ok(GroupKind::None),
+            "many" => Ok(GroupKind::Many),
+            _ => Err(format!("unknown kind '{}'. Options: one, none, many", s))
+        }
+    }
+}
+
+impl Display for GroupKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            GroupKind::One => "one",
+            GroupKind::None => "none",
+            GroupKind::Many => "many",
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct GroupConfig {
+    pub name: String,
+    pub kind: GroupKind,
+    pub count: usize,
+    pub size: Range<i32>,
+    pub answer_range: Range<i32>,
+    pub divider: Range<i32>,
+    pub max_factor: i32,
+    pub max_sums: i32,
+    pub weight: f64,
+    pub scoring: Scoring,
+    pub requires: Vec<String>,
+}
+
+const KEYS: [&str; 10] = [
+    "kind", "count", "size", "answer_range", "divider", "max_factor", "max_sums", "weight", "scoring", "requires",
+];
+
+impl GroupConfig {
+    #[allow(clippy::too_many_arguments)]
+    fn new(name: &str, kind: GroupKind, count: usize, size: Range<i32>, answer_range: Range<i32>,
+           divider: Range<i32>, max_factor: i32, max_sums: i32) -> GroupConfig {
+        GroupConfig {
+            name: name.to_string(), kind, count, size, answer_range, divider, max_factor, max_sums,
+            weight: 1.0, scoring: Scoring::Proportional, requires: Vec::new(),
+        }
+    }
+
+    fn requires(mut self, names: &[&str]) -> GroupConfig {
+        self.requires = names.iter().map(|a| a.to_string()).collect();
+        self
+    }
+
+    fn from_section(section: &Section) -> Result<GroupConfig, String> {
+        if let Some(key) = section.keys().find(|a| !KEYS.contains(a)) {
+            return Err(format!("[{}]: unknown key '{}'", section.name, key));
+        }
+
+        let kind: GroupKind = section.parse("kind")?;
+        let size = section.parse_range("size")?;
+        if size.start < 1 {
+            return Err(format!("[{}] size: matrices must have positive size", section.name));
+        }
+        let answer_range = section.parse_range("answer_range")?;
+        if answer_range == (0..1) {
+            return Err(format!("[{}] answer_range: must contain non-zero values", section.name));
+        }
+        let divider = section.parse_range("divider")?;
+        if kind != GroupKind::None && divider.contains(&0) {
+            return Err(format!("[{}] divider: must not contain zero for '{}' groups", section.name, kind));
+        }
+        let max_factor = section.parse("max_factor")?;
+        if max_factor < 1 {
+            return Err(format!("[{}] max_factor: must be positive", section.name));
+        }
+        let max_sums = section.parse("max_sums")?;
+        if max_sums < 1 {
+            return Err(format!("[{}] max_sums: must be positive", section.name));
+        }
+
+        let weight = section.parse_or("weight", 1.0)?;
+        if weight < 0.0 {
+            return Err(format!("[{}] weight: must not be negative", section.name));
+        }
+
+        Ok(GroupConfig {
+            name: section.name.clone(),
+            kind,
+            count: section.parse("count")?,
+            size,
+            answer_range,
+            divider,
+            max_factor,
+            max_sums,
+            weight,
+            scoring: section.parse_or("scoring", Scoring::Proportional)?,
+            requires: section.get("requires").unwrap_or_default()
+                .split(',')
+                .map(|a| a.trim().to_string())
+                .filter(|a| !a.is_empty())
+                .collect(),
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct SLAEConfig {
+    pub groups: Vec<GroupConfig>,
+}
+
+impl SLAEConfig {
+    pub fn from_config(config: &Config) -> Result<SLAEConfig, String> {
+        let groups = config.sections.iter()
+            .map(GroupConfig::from_section)
+            .collect::<Result<Vec<GroupConfig>, String>>()?;
+
+        Ok(SLAEConfig { groups })
+    }
+
+    pub fn scaled(mut self, factor: f64) -> SLAEConfig {
+        for group in &mut self.groups {
+            group.count = scale_count(group.count, factor);
+        }
+        self
+    }
+}
+
+impl Default for SLAEConfig {
+    fn default() -> Self {
+        SLAEConfig {
+            groups: vec![
+                GroupConfig::new("random one answer simple", GroupKind::One, 200, 1..5 + 1, -10..10, 1..2, 2, 2)
+                    .requires(&["hand-made"]),
+                GroupConfig::new("random one answer long", GroupKind::One, 40, 5..10, -100..100, 1..30, 10, 10)
+                    .requires(&["random one answer simple"]),
+                GroupConfig::new("random no answer short", GroupKind::None, 200, 2..5 + 1, -10..10, 1..2, 2, 2)
+                    .requires(&["hand-made"]),
+                GroupConfig::new("random no answer long", GroupKind::None, 40, 5..10, -100..100, 1..30, 10, 10)
+                    .requires(&["random no answer short"]),
+                GroupConfig::new("random many answer short", GroupKind::Many, 200, 2..5 + 1, -10..10, 1..2, 2, 2)
+                    .requires(&["hand-made"]),
+                GroupConfig::new("random many answer long", GroupKind::Many, 40, 5..6, -100..100, 1..30, 4, 10)
+                    .requires(&["random many answer short"]),
+            ]
+        }
+    }
+}
+
+impl Display for SLAEConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (idx, group) in self.groups.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "[{}]", group.name)?;
+            writeln!(f, "kind = {}", group.kind)?;
+            writeln!(f, "count = {}", group.count)?;
+            writeln!(f, "size = {}..{}", group.size.start, group.size.end)?;
+            writeln!(f, "answer_range = {}..{}", group.answer_range.start, group.answer_range.end)?;
+            writeln!(f, "divider = {}..{}", group.divider.start, group.divider.end)?;
+            writeln!(f, "max_factor = {}", group.max_factor)?;
+            writeln!(f, "max_sums = {}", group.max_sums)?;
+            writeln!(f, "weight = {}", group.weight)?;
+            writeln!(f, "scoring = {}", group.scoring)?;
+            if !group.requires.is_empty() {
+                writeln!(f, "requires = {}", group.requires.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
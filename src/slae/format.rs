@@ -0,0 +1,62 @@
+use crate::base::format::{FormatProfile, compare_bytes, describe_char};
+use crate::slae::Answer;
+
+pub const DEFAULT_PROFILE: FormatProfile = FormatProfile::Lenient;
+
+pub const PRECISION: usize = 4;
+
+pub fn render(answer: &Answer) -> String {
+    match answer {
+        Answer::FOUND(values) => values.iter().map(|a| format!("{:.*}\n", PRECISION, a)).collect(),
+        Answer::MANY => "many solutions\n".to_string(),
+        Answer::NONE => "no solutions\n".to_string(),
+    }
+}
+
+pub fn check_format(s: &str, expected: &Answer, profile: FormatProfile) -> Result<(), String> {
+    match profile {
+        FormatProfile::Lenient => Ok(()),
+        FormatProfile::LabSpec => check_lab_spec(s),
+        FormatProfile::ExactBytes => compare_bytes(&render(expected), s),
+    }
+}
+
+fn check_lab_spec(s: &str) -> Result<(), String> {
+    if s.contains("\r\n") {
+        return Err("Lines must end with '\\n', found '\\r\\n'".to_string());
+    }
+    let body = match s.strip_suffix('\n') {
+        Some(body) => body,
+        None => return Err("Missing trailing newline".to_string()),
+    };
+    if body == "many solutions" || body == "no solutions" {
+        return Ok(());
+    }
+
+    let mut problems = Vec::new();
+    for (idx, line) in body.split('\n').enumerate() {
+        let line_no = idx + 1;
+        if line.is_empty() {
+            problems.push(format!("Line {} is empty", line_no));
+            continue;
+        }
+        if let Some(c) = line.chars().find(|a| a.is_whitespace()) {
+            problems.push(format!(
+                "Line {}: values must be separated by '\\n', found {}", line_no, describe_char(c)
+            ));
+            continue;
+        }
+        let digits = line.split_once('.').map_or(0, |(_, fraction)| fraction.len());
+        if digits != PRECISION {
+            problems.push(format!(
+                "Line {}: '{}' has {} digits after the decimal point, expected {}", line_no, line, digits, PRECISION
+            ));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems.join("\n"))
+    }
+}
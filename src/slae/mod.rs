@@ -4,16 +4,23 @@ use std::str::FromStr;
 use rand::{Rng, RngCore};
 use rand::seq::SliceRandom;
 
-use crate::base::runner::TryFromVerbal;
+use crate::base::runner::{TryFromVerbal, VerbalInput};
+use crate::base::format::FormatProfile;
 use crate::base::tests_supply::{Test, TestsNode, TestsSupplier};
 use crate::slae::Answer::FOUND;
 use std::ops::Range;
+use crate::slae::config::{SLAEConfig, GroupConfig, GroupKind};
+
+pub mod config;
+pub mod format;
+pub mod residual;
 
 type MathResult = Result<(), ()>;
 
 #[derive(Clone, PartialEq)]
 pub struct Matrix {
     n: i32,
+    cols: i32,
     rows: Vec<i32>,
     matrix: Vec<f32>,
 }
@@ -22,6 +29,7 @@ impl Matrix {
     pub fn new(n: i32, matrix: Vec<f32>) -> Matrix {
         Matrix {
             n,
+            cols: n + 1,
             rows: (0..n).collect(),
             matrix,
         }
@@ -30,19 +38,38 @@ impl Matrix {
     pub fn new_empty(n: i32) -> Matrix {
         Matrix {
             n,
+            cols: n + 1,
             rows: (0..n).collect(),
             matrix: vec![0.0; (n * (n + 1)) as usize],
         }
     }
 
+    pub fn new_square(n: i32) -> Matrix {
+        Matrix {
+            n,
+            cols: n,
+            rows: (0..n).collect(),
+            matrix: vec![0.0; (n * n) as usize],
+        }
+    }
+
+    pub fn size(&self) -> i32 {
+        self.n
+    }
+
+    /// Storage row shown at every position, see `shuffle_matrix`
+    pub fn row_order(&self) -> &[i32] {
+        &self.rows
+    }
+
     fn ensure_bounds(&self, x: i32, y: i32) {
-        if x < 0 || x > self.n || y < 0 || y >= self.n {
+        if x < 0 || x >= self.cols || y < 0 || y >= self.n {
             panic!("Out of bounds")
         }
     }
 
     fn idx(&self, y: i32, x: i32) -> usize {
-        return (self.rows.get(y as usize).unwrap() * (self.n + 1) + x) as usize;
+        return (self.rows.get(y as usize).unwrap() * self.cols + x) as usize;
     }
 
     pub fn get_at(&self, y: i32, x: i32) -> i32 {
@@ -76,7 +103,7 @@ impl Matrix {
         let mut before = Vec::new();
 
         let mut failed = false;
-        for x in 0..self.n + 1 {
+        for x in 0..self.cols {
             let a = self.get_at(transmitter, x).checked_mul(factor);
             if a.is_none() {
                 failed = true;
@@ -114,7 +141,7 @@ impl Display for Matrix {
 
         result.push_str(&format!("{}\n", self.n));
         for i in 0..self.n {
-            for j in 0..self.n + 1 {
+            for j in 0..self.cols {
                 result.push_str(&format!("{} ", self.get_at(i, j)));
             }
             result.push('\n')
@@ -123,6 +150,8 @@ impl Display for Matrix {
     }
 }
 
+impl VerbalInput for Matrix {}
+
 #[derive(Clone)]
 pub enum Answer {
     MANY,
@@ -132,29 +161,21 @@ pub enum Answer {
 
 impl PartialEq<Self> for Answer {
     fn eq(&self, other: &Self) -> bool {
-        match self {
-            Answer::MANY => {
-                matches!(other, Answer::MANY)
-            }
-            Answer::NONE => {
-                matches!(other, Answer::NONE)
-            }
-            FOUND(e) => {
-
-                if let Answer::FOUND(me) = self {
-                    if me.len() != e.len() { return false; }
-
-                    for i in 0..me.len() {
-                        if f32::abs(me.get(i).unwrap() - e.get(i).unwrap()) > 0.0001  {
-                            return false;
-                        }
+        match (self, other) {
+            (Answer::MANY, Answer::MANY) => true,
+            (Answer::NONE, Answer::NONE) => true,
+            (FOUND(me), FOUND(e)) => {
+                if me.len() != e.len() { return false; }
+
+                for i in 0..me.len() {
+                    if f32::abs(me.get(i).unwrap() - e.get(i).unwrap()) > 0.0001  {
+                        return false;
                     }
-
-                    true
-                } else {
-                    return false;
                 }
+
+                true
             }
+            _ => false,
         }
     }
 }
@@ -163,6 +184,7 @@ impl Eq for Answer {}
 
 impl TryFromVerbal for Answer {
     fn try_from(s: &str) -> Result<Self, String> {
+        let s = s.trim();
         if s == "no solutions" {
             return Ok(Answer::NONE);
         }
@@ -179,6 +201,10 @@ impl TryFromVerbal for Answer {
 
         Ok(FOUND(answer))
     }
+
+    fn check_format(s: &str, expected: &Self, profile: FormatProfile) -> Result<(), String> {
+        format::check_format(s, expected, profile)
+    }
 }
 
 impl Display for Answer {
@@ -201,11 +227,13 @@ impl Display for Answer {
     }
 }
 
-pub struct SLAESupplier {}
+pub struct SLAESupplier {
+    config: SLAEConfig,
+}
 
 impl SLAESupplier {
-    pub fn new() -> SLAESupplier {
-        SLAESupplier {}
+    pub fn new(config: SLAEConfig) -> SLAESupplier {
+        SLAESupplier { config }
     }
 }
 
@@ -214,203 +242,299 @@ impl TestsSupplier<Matrix, Answer> for SLAESupplier {
         let mut result = TestsNode::new("root");
 
         self.hand_made(result.child("hand-made"));
-        self.random_one_answer(
-            result.child("random one answer simple"),
-            rng,
-            200,
-            1..5+1,
-            -10..10,
-            1..2,
-            2,
-            2
-        );
-        self.random_one_answer(
-            result.child("random one answer long"),
-            rng,
-            40,
-            5..10,
-            -100..100,
-            1..30,
-            10,
-            10
-        );
-
-        self.random_no_answer(
-            result.child("random no answer short"),
-            rng,
-            200,
-            2..5+1,
-            -10..10,
-            1..2,
-            2,
-            2
-        );
-        self.random_no_answer(
-            result.child("random no answer long"),
-            rng,
-            40,
-            5..10,
-            -100..100,
-            1..30,
-            10,
-            10
-        );
-        self.random_many_answer(
-            result.child("random many answer short"),
-            rng,
-            200,
-            2..5+1,
-            -10..10,
-            1..2,
-            2,
-            2
-        );
-        self.random_many_answer(
-            result.child("random many answer long"),
-            rng,
-            40,
-            5..6,
-            -100..100,
-            1..30,
-            4,
-            10
-        );
+        for group in &self.config.groups {
+            let node = result.child(group.name.clone());
+            node.set_weight(group.weight).set_scoring(group.scoring);
+            for name in &group.requires {
+                node.require(name.clone());
+            }
+            let config = group.clone();
+            match group.kind {
+                GroupKind::One => node.generate(group.count, rng, move |rng| random_one_answer(rng, &config)),
+                GroupKind::None => node.generate(group.count, rng, move |rng| random_no_answer(rng, &config)),
+                GroupKind::Many => node.generate(group.count, rng, move |rng| random_many_answer(rng, &config)),
+            };
+        }
         result
     }
 }
 
-fn shuffle_matrix(matrix: &mut Matrix, rng: &mut dyn RngCore, max_factor: i32, max_sums: i32) {
+pub struct RowSum {
+    pub transmitter: i32,
+    pub receiver: i32,
+    pub factor: i32,
+}
+
+/// Returns the row sums that were actually applied. They are applied before rows get
+/// shuffled, so the final order is available through `Matrix::row_order`
+pub fn shuffle_matrix(matrix: &mut Matrix, rng: &mut dyn RngCore, max_factor: i32, max_sums: i32) -> Vec<RowSum> {
+    let mut applied = Vec::new();
     let sums = rng.gen_range(0..max_sums);
     for _ in 0..sums {
         let a = rng.gen_range(0..matrix.n);
         let b = rng.gen_range(0..matrix.n);
         let factor = rng.gen_range(0..max_factor);
 
-        matrix.sum_rows(a, b, factor);
+        if matrix.sum_rows(a, b, factor).is_ok() {
+            applied.push(RowSum { transmitter: a, receiver: b, factor });
+        }
     }
 
     if rng.gen_bool(0.9) {
         matrix.rows.shuffle(rng)
     }
+
+    applied
 }
 
 impl SLAESupplier {
 
-    fn nice_floatizible(&self, rng: &mut dyn RngCore, range: Range<i32>) -> i32 {
-        let mut ans = rng.gen_range(range.clone());
-        while ((ans as f32) as i32)!= ans  {
-            ans = rng.gen_range(range.clone());
-        }
+    fn hand_made(&self, node: &mut TestsNode<Matrix, Answer>) {
+        node.child("dependent rows")
+            .add_test(Test::new(
+                Matrix::new(3,
+                            vec![
+                                1.0, 2.0, 3.0, 4.0,
+                                5.0, 6.0, 7.0, 8.0,
+                                9.0, 10.0, 11.0, 12.0,
+                            ],
+                ),
+                Answer::MANY,
+            ));
+
+        node.child("1x1")
+            .add_test(Test::new(Matrix::new(1, vec![2.0, 4.0]), FOUND(vec![2.0])))
+            .add_test(Test::new(Matrix::new(1, vec![-4.0, 2.0]), FOUND(vec![-0.5])))
+            .add_test(Test::new(Matrix::new(1, vec![0.0, 0.0]), Answer::MANY))
+            .add_test(Test::new(Matrix::new(1, vec![0.0, 5.0]), Answer::NONE));
+
+        node.child("zero pivot")
+            .add_test(Test::new(
+                Matrix::new(2,
+                            vec![
+                                0.0, 1.0, 2.0,
+                                1.0, 0.0, 3.0,
+                            ],
+                ),
+                FOUND(vec![3.0, 2.0]),
+            ))
+            .add_test(Test::new(
+                Matrix::new(3,
+                            vec![
+                                0.0, 0.0, 1.0, 3.0,
+                                0.0, 1.0, 0.0, 2.0,
+                                1.0, 0.0, 0.0, 1.0,
+                            ],
+                ),
+                FOUND(vec![1.0, 2.0, 3.0]),
+            ))
+            .add_test(Test::new(
+                Matrix::new(3,
+                            vec![
+                                0.0, 2.0, 1.0, 3.0,
+                                1.0, 1.0, 1.0, 3.0,
+                                2.0, 1.0, 0.0, 3.0,
+                            ],
+                ),
+                FOUND(vec![1.0, 1.0, 1.0]),
+            ));
+
+        node.child("duplicate rows")
+            .add_test(Test::new(
+                Matrix::new(2,
+                            vec![
+                                1.0, 2.0, 3.0,
+                                1.0, 2.0, 3.0,
+                            ],
+                ),
+                Answer::MANY,
+            ))
+            .add_test(Test::new(
+                Matrix::new(3,
+                            vec![
+                                1.0, 1.0, 1.0, 3.0,
+                                2.0, 2.0, 2.0, 6.0,
+                                1.0, -1.0, 0.0, 0.0,
+                            ],
+                ),
+                Answer::MANY,
+            ))
+            .add_test(Test::new(
+                Matrix::new(2,
+                            vec![
+                                1.0, 2.0, 3.0,
+                                1.0, 2.0, 4.0,
+                            ],
+                ),
+                Answer::NONE,
+            ));
+
+        node.child("negative zero")
+            .add_test(Test::new(Matrix::new(1, vec![-3.0, 0.0]), FOUND(vec![0.0])))
+            .add_test(Test::new(
+                Matrix::new(2,
+                            vec![
+                                2.0, 0.0, 0.0,
+                                0.0, -5.0, 0.0,
+                            ],
+                ),
+                FOUND(vec![0.0, 0.0]),
+            ))
+            .add_test(Test::new(
+                Matrix::new(2,
+                            vec![
+                                1.0, 2.0, 2.0,
+                                3.0, 4.0, 6.0,
+                            ],
+                ),
+                FOUND(vec![2.0, 0.0]),
+            ));
+
+        node.child("contradicting last row")
+            .add_test(Test::new(
+                Matrix::new(3,
+                            vec![
+                                1.0, 0.0, 0.0, 1.0,
+                                0.0, 1.0, 0.0, 2.0,
+                                0.0, 0.0, 0.0, 1.0,
+                            ],
+                ),
+                Answer::NONE,
+            ))
+            .add_test(Test::new(
+                Matrix::new(2,
+                            vec![
+                                1.0, 1.0, 2.0,
+                                2.0, 2.0, 5.0,
+                            ],
+                ),
+                Answer::NONE,
+            ))
+            .add_test(Test::new(
+                Matrix::new(3,
+                            vec![
+                                1.0, 2.0, 3.0, 6.0,
+                                2.0, 3.0, 4.0, 9.0,
+                                3.0, 5.0, 7.0, 16.0,
+                            ],
+                ),
+                Answer::NONE,
+            ));
+
+        node.child("large magnitudes")
+            .add_test(Test::new(
+                Matrix::new(2,
+                            vec![
+                                1000000.0, 0.0, 2000000.0,
+                                0.0, 3000000.0, 9000000.0,
+                            ],
+                ),
+                FOUND(vec![2.0, 3.0]),
+            ))
+            .add_test(Test::new(
+                Matrix::new(2,
+                            vec![
+                                16000000.0, 8000000.0, 24000000.0,
+                                8000000.0, 16000000.0, 24000000.0,
+                            ],
+                ),
+                FOUND(vec![1.0, 1.0]),
+            ));
+
+        node.child("small magnitudes")
+            .add_test(Test::new(Matrix::new(1, vec![1000000.0, 10000.0]), FOUND(vec![0.01])))
+            .add_test(Test::new(
+                Matrix::new(2,
+                            vec![
+                                10000.0, 0.0, 125.0,
+                                0.0, 20000.0, 50.0,
+                            ],
+                ),
+                FOUND(vec![0.0125, 0.0025]),
+            ));
+
+        node.child("near integer roots")
+            .add_test(Test::new(Matrix::new(1, vec![1000.0, 1001.0]), FOUND(vec![1.001])))
+            .add_test(Test::new(
+                Matrix::new(2,
+                            vec![
+                                1000.0, 0.0, 1001.0,
+                                0.0, 1000.0, -999.0,
+                            ],
+                ),
+                FOUND(vec![1.001, -0.999]),
+            ));
+    }
+}
 
-        return ans;
+fn nice_floatizible(rng: &mut dyn RngCore, range: Range<i32>) -> i32 {
+    let mut ans = rng.gen_range(range.clone());
+    while ((ans as f32) as i32)!= ans  {
+        ans = rng.gen_range(range.clone());
     }
 
-    fn non_zero(&self, rng: &mut dyn RngCore, range: Range<i32>) -> i32 {
-        let mut ans = 0;
-        while ans == 0 {
-            ans = self.nice_floatizible(rng, range.clone());
-        }
-        return ans;
+    return ans;
+}
+
+fn non_zero(rng: &mut dyn RngCore, range: Range<i32>) -> i32 {
+    let mut ans = 0;
+    while ans == 0 {
+        ans = nice_floatizible(rng, range.clone());
     }
+    return ans;
+}
 
-    fn hand_made(&self, node: &mut TestsNode<Matrix, Answer>) {
-        node.add_test(Test::new(
-            Matrix::new(3,
-                        vec![
-                            1.0, 2.0, 3.0, 4.0,
-                            5.0, 6.0, 7.0, 8.0,
-                            9.0, 10.0, 11.0, 12.0,
-                        ],
-            ),
-            Answer::MANY,
-        ));
+fn random_many_answer(rng: &mut dyn RngCore, group: &GroupConfig) -> Test<Matrix, Answer> {
+    let mut matrix = Matrix::new_empty(rng.gen_range(group.size.clone()));
+    for i in 0..matrix.n-1 {
+        matrix.set_at(i, i, non_zero(rng, group.divider.clone()));
+    }
+    for i in 0..matrix.n-1 {
+        matrix.set_at(i, matrix.n, non_zero(rng, group.answer_range.clone()));
     }
-    fn random_many_answer(&self,
-                        node: &mut TestsNode<Matrix, Answer>,
-                        rng: &mut dyn RngCore,
-                        num: usize,
-                        size: Range<i32>,
-                        answer_range: Range<i32>,
-                        divider: Range<i32>,
-                        max_factor: i32,
-                        max_sums: i32
-
-    ) {
-        for i in 0..num {
-            let mut matrix = Matrix::new_empty(rng.gen_range(size.clone()));
-            for i in 0..matrix.n-1 {
-                matrix.set_at(i, i, self.non_zero(rng, divider.clone()));
-            }
-            for i in 0..matrix.n-1 {
-                matrix.set_at(i, matrix.n, self.non_zero(rng, answer_range.clone()));
-            }
 
-            let sums = matrix.n * max_sums;
-            shuffle_matrix(&mut matrix, rng, max_factor, sums);
+    let sums = matrix.n * group.max_sums;
+    shuffle_matrix(&mut matrix, rng, group.max_factor, sums);
 
-            node.add_test(Test::new(matrix, Answer::MANY));
-        }
+    Test::new(matrix, Answer::MANY)
+}
+
+fn random_no_answer(rng: &mut dyn RngCore, group: &GroupConfig) -> Test<Matrix, Answer> {
+    let mut matrix = Matrix::new_empty(rng.gen_range(group.size.clone()));
+    for i in 0..matrix.n-1 {
+        matrix.set_at(i, i, nice_floatizible(rng, group.divider.clone()));
+    }
+    for i in 0..matrix.n-1 {
+        matrix.set_at(i, matrix.n, nice_floatizible(rng, group.answer_range.clone()));
     }
-    fn random_no_answer(&self,
-                         node: &mut TestsNode<Matrix, Answer>,
-                         rng: &mut dyn RngCore,
-                         num: usize,
-                         size: Range<i32>,
-                         answer_range: Range<i32>,
-                         divider: Range<i32>,
-                         max_factor: i32,
-                         max_sums: i32
-
-    ) {
-        for _ in 0..num {
-            let mut matrix = Matrix::new_empty(rng.gen_range(size.clone()));
-            for i in 0..matrix.n-1 {
-                matrix.set_at(i, i, self.nice_floatizible(rng, divider.clone()));
-            }
-            for i in 0..matrix.n-1 {
-                matrix.set_at(i, matrix.n, self.nice_floatizible(rng, answer_range.clone()));
-            }
 
 
-            let mut ans = 0;
-            while ans == 0 {
-                ans = self.nice_floatizible(rng, answer_range.clone());
-            }
-            matrix.set_at(matrix.n - 1, matrix.n, ans);
+    let mut ans = 0;
+    while ans == 0 {
+        ans = nice_floatizible(rng, group.answer_range.clone());
+    }
+    matrix.set_at(matrix.n - 1, matrix.n, ans);
 
-            let sums = matrix.n * max_sums;
-            shuffle_matrix(&mut matrix, rng, max_factor, sums);
+    let sums = matrix.n * group.max_sums;
+    shuffle_matrix(&mut matrix, rng, group.max_factor, sums);
 
-            node.add_test(Test::new(matrix, Answer::NONE));
-        }
+    Test::new(matrix, Answer::NONE)
+}
+
+fn random_one_answer(rng: &mut dyn RngCore, group: &GroupConfig) -> Test<Matrix, Answer> {
+    let mut matrix = Matrix::new_empty(rng.gen_range(group.size.clone()));
+    let mut answer = Vec::new();
+    for i in 0..matrix.n {
+        matrix.set_at(i, i, nice_floatizible(rng, group.divider.clone()));
     }
-    fn random_one_answer(&self,
-                         node: &mut TestsNode<Matrix, Answer>,
-                         rng: &mut dyn RngCore,
-        num: usize,
-        size: Range<i32>,
-        answer_range: Range<i32>,
-        divider: Range<i32>,
-        max_factor: i32,
-        max_sums: i32
-
-    ) {
-        for _ in 0..num {
-            let mut matrix = Matrix::new_empty(rng.gen_range(size.clone()));
-            let mut answer = Vec::new();
-            for i in 0..matrix.n {
-                matrix.set_at(i, i, self.nice_floatizible(rng, divider.clone()));
-            }
-            for i in 0..matrix.n {
-                matrix.set_at(i, matrix.n, self.nice_floatizible(rng, answer_range.clone()));
+    for i in 0..matrix.n {
+        matrix.set_at(i, matrix.n, nice_floatizible(rng, group.answer_range.clone()));
 
-                answer.push((matrix.get_at(i, matrix.n) as f32) / (matrix.get_at(i, i) as f32));
-            }
+        answer.push((matrix.get_at(i, matrix.n) as f32) / (matrix.get_at(i, i) as f32));
+    }
 
-            let sums = matrix.n * max_sums;
-            shuffle_matrix(&mut matrix, rng, max_factor, sums);
+    let sums = matrix.n * group.max_sums;
+    shuffle_matrix(&mut matrix, rng, group.max_factor, sums);
 
-            node.add_test(Test::new(matrix, Answer::FOUND(answer)));
-        }
-    }
+    Test::new(matrix, Answer::FOUND(answer))
 }
@@ -0,0 +1,50 @@
+use crate::slae::{Answer, Matrix};
+
+pub const DEFAULT_TOLERANCE: f64 = 1e-5;
+
+fn norm(values: impl Iterator<Item=f64>) -> f64 {
+    values.fold(0.0, |acc, a| f64::max(acc, a.abs()))
+}
+
+pub fn check_residual(matrix: &Matrix, expected: &Answer, actual: &Answer, tolerance: f64) -> Result<(), String> {
+    let solution = match (expected, actual) {
+        (Answer::FOUND(_), Answer::FOUND(solution)) => solution,
+        _ => {
+            if std::mem::discriminant(expected) == std::mem::discriminant(actual) {
+                return Ok(());
+            }
+            return Err(format!("Expected {} but got {}", expected, actual));
+        }
+    };
+
+    if solution.len() != matrix.n as usize {
+        return Err(format!("Expected {} values but got {}", matrix.n, solution.len()));
+    }
+
+    let solution: Vec<f64> = solution.iter().map(|a| *a as f64).collect();
+
+    let matrix_norm = norm((0..matrix.n).map(|y| (0..matrix.n).map(|x| (matrix.get_at(y, x) as f64).abs()).sum()));
+    let rhs_norm = norm((0..matrix.n).map(|y| matrix.get_at(y, matrix.n) as f64));
+    let scale = matrix_norm * norm(solution.iter().cloned()) + rhs_norm;
+
+    let mut worst: Option<(i32, f64)> = None;
+    for y in 0..matrix.n {
+        let lhs: f64 = (0..matrix.n).map(|x| matrix.get_at(y, x) as f64 * solution[x as usize]).sum();
+        let residual = (lhs - matrix.get_at(y, matrix.n) as f64).abs();
+        if worst.is_none_or(|(_, w)| residual > w) {
+            worst = Some((y, residual));
+        }
+    }
+
+    if let Some((row, residual)) = worst {
+        let relative = if scale == 0.0 { residual } else { residual / scale };
+        if relative > tolerance {
+            return Err(format!(
+                "Relative residual {:e} exceeds tolerance {:e}. Worst equation is #{} with absolute residual {:e}",
+                relative, tolerance, row + 1, residual
+            ));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,323 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use rand::{Rng, RngCore};
+use rand::seq::SliceRandom;
+
+use crate::base::runner::{TryFromVerbal, VerbalInput};
+use crate::base::scale::scale_count;
+use crate::base::tests_supply::{Test, TestsNode, TestsSupplier};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ElementKind {
+    Int,
+    Float,
+    Record,
+}
+
+impl ElementKind {
+    fn name(&self) -> &'static str {
+        match self {
+            ElementKind::Int => "int",
+            ElementKind::Float => "float",
+            ElementKind::Record => "record",
+        }
+    }
+
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        match self {
+            ElementKind::Int => i64::from_str(a).unwrap().cmp(&i64::from_str(b).unwrap()),
+            ElementKind::Float => f64::from_str(a).unwrap().partial_cmp(&f64::from_str(b).unwrap()).unwrap(),
+            ElementKind::Record => record_key(a).cmp(record_key(b)),
+        }
+    }
+}
+
+fn record_key(record: &str) -> &str {
+    record.split_whitespace().next().unwrap_or("")
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Ascending,
+    Descending,
+}
+
+impl Order {
+    fn name(&self) -> &'static str {
+        match self {
+            Order::Ascending => "asc",
+            Order::Descending => "desc",
+        }
+    }
+
+    fn reversed(&self) -> Order {
+        match self {
+            Order::Ascending => Order::Descending,
+            Order::Descending => Order::Ascending,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SortInput {
+    kind: ElementKind,
+    order: Order,
+    items: Vec<String>,
+}
+
+impl SortInput {
+    fn sorted(&self, order: Order) -> Vec<String> {
+        let mut items = self.items.clone();
+        // sort_by is stable, so records with equal keys keep their input order in both directions
+        items.sort_by(|a, b| {
+            let ordering = self.kind.compare(a, b);
+            if order == Order::Descending { ordering.reverse() } else { ordering }
+        });
+        items
+    }
+}
+
+impl Display for SortInput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} {} {}", self.kind.name(), self.order.name(), self.items.len())?;
+        for item in &self.items {
+            writeln!(f, "{}", item)?;
+        }
+        Ok(())
+    }
+}
+
+impl VerbalInput for SortInput {
+    fn is_output_complete(&self, output: &[u8]) -> bool {
+        output.iter().filter(|a| **a == b'\n').count() >= self.items.len()
+    }
+}
+
+#[derive(Clone)]
+pub struct SortOutput {
+    lines: Vec<String>,
+}
+
+fn same_line(a: &str, b: &str) -> bool {
+    if a == b {
+        return true;
+    }
+    match (f64::from_str(a), f64::from_str(b)) {
+        (Ok(a), Ok(b)) => (a - b).abs() <= 1e-9 * f64::max(1.0, a.abs()),
+        _ => false,
+    }
+}
+
+impl PartialEq for SortOutput {
+    fn eq(&self, other: &Self) -> bool {
+        self.lines.len() == other.lines.len()
+            && self.lines.iter().zip(other.lines.iter()).all(|(a, b)| same_line(a, b))
+    }
+}
+
+impl Eq for SortOutput {}
+
+impl TryFromVerbal for SortOutput {
+    fn try_from(s: &str) -> Result<Self, String> {
+        let lines = s.lines()
+            .map(|a| a.split_whitespace().collect::<Vec<&str>>().join(" "))
+            .filter(|a| !a.is_empty())
+            .collect();
+
+        Ok(SortOutput { lines })
+    }
+}
+
+impl Display for SortOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn check(input: &SortInput, expected: &SortOutput, actual: &SortOutput) -> Result<(), String> {
+    if expected == actual {
+        return Ok(());
+    }
+    if expected.lines.len() != actual.lines.len() {
+        return Err(format!("Expected {} lines but got {}", expected.lines.len(), actual.lines.len()));
+    }
+
+    let idx = expected.lines.iter()
+        .zip(actual.lines.iter())
+        .position(|(a, b)| !same_line(a, b))
+        .unwrap();
+
+    if input.kind == ElementKind::Record {
+        let mut given = input.items.clone();
+        let mut returned = actual.lines.clone();
+        given.sort();
+        returned.sort();
+        let keys_match = expected.lines.iter()
+            .zip(actual.lines.iter())
+            .all(|(a, b)| record_key(a) == record_key(b));
+        if given == returned && keys_match {
+            return Err(format!(
+                "Sort is not stable: records with key '{}' are not in input order (line {})",
+                record_key(&expected.lines[idx]), idx + 1
+            ));
+        }
+    }
+
+    Err(format!("Line {}: expected '{}' but got '{}'", idx + 1, expected.lines[idx], actual.lines[idx]))
+}
+
+#[derive(Clone, Copy)]
+enum Layout {
+    Random,
+    Sorted,
+    Reversed,
+    AllEqual,
+    DuplicateHeavy,
+}
+
+pub struct SortSupplier {
+    scale: f64,
+}
+
+impl SortSupplier {
+    pub fn new(scale: f64) -> SortSupplier {
+        SortSupplier { scale }
+    }
+}
+
+impl TestsSupplier<SortInput, SortOutput> for SortSupplier {
+    fn supply_tests(&self, rng: &mut dyn RngCore) -> TestsNode<SortInput, SortOutput> {
+        let mut result = TestsNode::new("root");
+
+        self.hand_made(result.child("hand-made"));
+
+        for kind in [ElementKind::Int, ElementKind::Float, ElementKind::Record] {
+            let node = result.child(kind.name());
+            self.random_group(node.child("random"), rng, kind, Layout::Random, 100, 1..50);
+            self.random_group(node.child("already sorted"), rng, kind, Layout::Sorted, 30, 1..50);
+            self.random_group(node.child("reverse sorted"), rng, kind, Layout::Reversed, 30, 1..50);
+            self.random_group(node.child("all equal"), rng, kind, Layout::AllEqual, 20, 1..50);
+            self.random_group(node.child("duplicate heavy"), rng, kind, Layout::DuplicateHeavy, 50, 10..200);
+        }
+
+        self.stability(result.child("stability"), rng, 100);
+
+        let large = result.child("large");
+        self.random_group(large.child("int"), rng, ElementKind::Int, Layout::Random, 3, 100_000..100_001);
+        self.random_group(large.child("float"), rng, ElementKind::Float, Layout::Random, 3, 50_000..50_001);
+        self.random_group(large.child("record"), rng, ElementKind::Record, Layout::DuplicateHeavy, 3, 50_000..50_001);
+
+        result
+    }
+}
+
+fn make_test(kind: ElementKind, order: Order, items: Vec<String>) -> Test<SortInput, SortOutput> {
+    let input = SortInput { kind, order, items };
+    let lines = input.sorted(order);
+    Test::new(input, SortOutput { lines })
+}
+
+fn random_word(rng: &mut dyn RngCore, length: std::ops::Range<usize>) -> String {
+    let length = rng.gen_range(length);
+    (0..length).map(|_| rng.gen_range(b'a'..=b'z') as char).collect()
+}
+
+fn random_item(rng: &mut dyn RngCore, kind: ElementKind, idx: usize) -> String {
+    match kind {
+        ElementKind::Int => rng.gen_range(-1_000_000_000..1_000_000_000).to_string(),
+        ElementKind::Float => format!("{:.3}", rng.gen_range(-1_000_000.0..1_000_000.0)),
+        ElementKind::Record => format!("{} {}{}", random_word(rng, 1..8), random_word(rng, 1..5), idx),
+    }
+}
+
+impl SortSupplier {
+
+    fn random_order(&self, rng: &mut dyn RngCore) -> Order {
+        if rng.gen_bool(0.5) { Order::Ascending } else { Order::Descending }
+    }
+
+    fn hand_made(&self, node: &mut TestsNode<SortInput, SortOutput>) {
+        let strings = |a: &[&str]| a.iter().map(|a| a.to_string()).collect::<Vec<String>>();
+
+        node.child("single element")
+            .add_test(make_test(ElementKind::Int, Order::Ascending, strings(&["42"])))
+            .add_test(make_test(ElementKind::Float, Order::Descending, strings(&["-0.5"])))
+            .add_test(make_test(ElementKind::Record, Order::Ascending, strings(&["key value"])));
+
+        node.child("two elements")
+            .add_test(make_test(ElementKind::Int, Order::Ascending, strings(&["2", "1"])))
+            .add_test(make_test(ElementKind::Int, Order::Descending, strings(&["1", "2"])))
+            .add_test(make_test(ElementKind::Float, Order::Ascending, strings(&["0.001", "-0.001"])));
+
+        node.child("extreme values")
+            .add_test(make_test(ElementKind::Int, Order::Ascending, strings(&["2147483647", "-2147483648", "0"])))
+            .add_test(make_test(ElementKind::Float, Order::Descending, strings(&["1e30", "-1e30", "0", "1e-30"])));
+
+        node.child("string keys")
+            .add_test(make_test(ElementKind::Record, Order::Ascending, strings(&["b 1", "ab 2", "a 3", "abc 4"])))
+            .add_test(make_test(ElementKind::Record, Order::Descending, strings(&["a 1", "b 2", "a 3", "b 4"])));
+    }
+
+    fn random_group(&self,
+                    node: &mut TestsNode<SortInput, SortOutput>,
+                    rng: &mut dyn RngCore,
+                    kind: ElementKind,
+                    layout: Layout,
+                    num: usize,
+                    size: std::ops::Range<usize>
+    ) {
+        for _ in 0..scale_count(num, self.scale) {
+            let n = rng.gen_range(size.clone());
+            let order = self.random_order(rng);
+
+            let items = match layout {
+                Layout::AllEqual => {
+                    let item = random_item(rng, kind, 0);
+                    match kind {
+                        ElementKind::Record => {
+                            let key = record_key(&item).to_string();
+                            (0..n).map(|idx| format!("{} {}", key, idx)).collect()
+                        }
+                        _ => vec![item; n]
+                    }
+                }
+                Layout::DuplicateHeavy => {
+                    let pool: Vec<String> = (0..rng.gen_range(2..6)).map(|idx| random_item(rng, kind, idx)).collect();
+                    (0..n).map(|idx| {
+                        let item = pool.choose(rng).unwrap();
+                        match kind {
+                            ElementKind::Record => format!("{} {}", record_key(item), idx),
+                            _ => item.clone()
+                        }
+                    }).collect()
+                }
+                _ => (0..n).map(|idx| random_item(rng, kind, idx)).collect(),
+            };
+
+            let items = match layout {
+                Layout::Sorted => SortInput { kind, order, items }.sorted(order),
+                Layout::Reversed => SortInput { kind, order, items }.sorted(order.reversed()),
+                _ => items,
+            };
+
+            node.add_test(make_test(kind, order, items));
+        }
+    }
+
+    fn stability(&self, node: &mut TestsNode<SortInput, SortOutput>, rng: &mut dyn RngCore, num: usize) {
+        for _ in 0..scale_count(num, self.scale) {
+            let n = rng.gen_range(5..100);
+            let keys: Vec<String> = (0..rng.gen_range(2..5)).map(|_| random_word(rng, 1..4)).collect();
+            let items = (0..n)
+                .map(|idx| format!("{} {}", keys.choose(rng).unwrap(), idx))
+                .collect();
+
+            node.add_test(make_test(ElementKind::Record, self.random_order(rng), items));
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use std::collections::{BTreeMap, BinaryHeap, VecDeque};
+use std::cmp::Reverse;
+use std::fmt::{Display, Formatter};
+
+use rand::{Rng, RngCore};
+use rand::seq::SliceRandom;
+
+use crate::base::checker::{Checker, Verdict};
+use crate::base::runner::{TryFromVerbal, VerbalInput};
+use crate::base::scale::scale_count;
+use crate::base::tests_supply::{Test, TestsNode, TestsSupplier};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Structure {
+    Map,
+    List,
+    Heap,
+}
+
+impl Structure {
+    fn name(&self) -> &'static str {
+        match self {
+            Structure::Map => "map",
+            Structure::List => "list",
+            Structure::Heap => "heap",
+        }
+    }
+
+    /// Every script ends with it, so there is always something to read
+    fn final_command(&self) -> &'static str {
+        match self {
+            Structure::Heap => "size",
+            _ => "print",
+        }
+    }
+}
+
+/// Reference implementations. `execute` returns the line the command prints, if any
+enum Model {
+    Map(BTreeMap<String, i64>),
+    List(VecDeque<i64>),
+    Heap(BinaryHeap<Reverse<i64>>),
+}
+
+fn or_empty(value: Option<i64>) -> String {
+    value.map_or_else(|| "empty".to_string(), |a| a.to_string())
+}
+
+impl Model {
+    fn new(structure: Structure) -> Model {
+        match structure {
+            Structure::Map => Model::Map(BTreeMap::new()),
+            Structure::List => Model::List(VecDeque::new()),
+            Structure::Heap => Model::Heap(BinaryHeap::new()),
+        }
+    }
+
+    fn execute(&mut self, command: &str) -> Option<String> {
+        let words: Vec<&str> = command.split(' ').collect();
+        let number = |idx: usize| words[idx].parse::<i64>().unwrap();
+        match self {
+            Model::Map(map) => match words[0] {
+                "insert" => {
+                    map.insert(words[1].to_string(), number(2));
+                    None
+                }
+                "delete" => {
+                    map.remove(words[1]);
+                    None
+                }
+                "get" => Some(map.get(words[1]).map_or_else(|| "none".to_string(), |a| a.to_string())),
+                "size" => Some(map.len().to_string()),
+                _ => Some(if map.is_empty() {
+                    "empty".to_string()
+                } else {
+                    map.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<String>>().join(" ")
+                }),
+            },
+            Model::List(list) => match words[0] {
+                "push_front" => {
+                    list.push_front(number(1));
+                    None
+                }
+                "push_back" => {
+                    list.push_back(number(1));
+                    None
+                }
+                "pop_front" => Some(or_empty(list.pop_front())),
+                "pop_back" => Some(or_empty(list.pop_back())),
+                "remove" => {
+                    if let Some(idx) = list.iter().position(|a| *a == number(1)) {
+                        list.remove(idx);
+                    }
+                    None
+                }
+                "size" => Some(list.len().to_string()),
+                _ => Some(if list.is_empty() {
+                    "empty".to_string()
+                } else {
+                    list.iter().map(|a| a.to_string()).collect::<Vec<String>>().join(" ")
+                }),
+            },
+            Model::Heap(heap) => match words[0] {
+                "push" => {
+                    heap.push(Reverse(number(1)));
+                    None
+                }
+                "pop" => Some(or_empty(heap.pop().map(|a| a.0))),
+                "top" => Some(or_empty(heap.peek().map(|a| a.0))),
+                _ => Some(heap.len().to_string()),
+            },
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Script {
+    structure: Structure,
+    commands: Vec<String>,
+    /// Indices of the commands which print a line
+    queries: Vec<usize>,
+}
+
+impl Display for Script {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} {}", self.structure.name(), self.commands.len())?;
+        for command in &self.commands {
+            writeln!(f, "{}", command)?;
+        }
+        Ok(())
+    }
+}
+
+impl VerbalInput for Script {
+    fn is_output_complete(&self, output: &[u8]) -> bool {
+        output.iter().filter(|a| **a == b'\n').count() >= self.queries.len()
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct ScriptOutput {
+    lines: Vec<String>,
+}
+
+impl TryFromVerbal for ScriptOutput {
+    fn try_from(s: &str) -> Result<Self, String> {
+        let mut lines: Vec<String> = s.lines().map(|a| a.trim().to_string()).collect();
+        while lines.last().is_some_and(|a| a.is_empty()) {
+            lines.pop();
+        }
+        Ok(ScriptOutput { lines })
+    }
+}
+
+impl Display for ScriptOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Hash maps may print their pairs in any order
+fn same_line(structure: Structure, command: &str, expected: &str, actual: &str) -> bool {
+    if structure == Structure::Map && command == "print" {
+        let mut a: Vec<&str> = expected.split_whitespace().collect();
+        let mut b: Vec<&str> = actual.split_whitespace().collect();
+        a.sort_unstable();
+        b.sort_unstable();
+        return a == b;
+    }
+    expected.split_whitespace().eq(actual.split_whitespace())
+}
+
+/// Scripts are graded by the share of correctly answered queries, the first mismatch is reported
+pub struct ScriptChecker;
+
+impl Checker<Script, ScriptOutput> for ScriptChecker {
+    fn check(&self, input: &Script, expected: &ScriptOutput, actual: &ScriptOutput) -> Result<Verdict, String> {
+        if actual.lines.len() > expected.lines.len() {
+            return Ok(Verdict::WrongAnswer(format!("Expected {} lines but got {}", expected.lines.len(), actual.lines.len())));
+        }
+        let mut first_error = None;
+        let mut correct = 0;
+        for (idx, command_idx) in input.queries.iter().enumerate() {
+            let command = &input.commands[*command_idx];
+            let error = match actual.lines.get(idx) {
+                Some(line) if same_line(input.structure, command, &expected.lines[idx], line) => None,
+                Some(line) => Some(format!(
+                    "Command #{} '{}': expected '{}' but got '{}'", command_idx + 1, command, expected.lines[idx], line
+                )),
+                None => Some(format!(
+                    "Output ended after {} lines, command #{} '{}' printed nothing", idx, command_idx + 1, command
+                )),
+            };
+            match error {
+                Some(error) => { first_error.get_or_insert(error); }
+                None => correct += 1,
+            }
+        }
+        Ok(match first_error {
+            None => Verdict::Ok,
+            Some(message) if correct == 0 => Verdict::WrongAnswer(message),
+            Some(message) => Verdict::Partial { score: correct as f64 / input.queries.len() as f64, message },
+        })
+    }
+}
+
+fn make_test(structure: Structure, mut commands: Vec<String>) -> Test<Script, ScriptOutput> {
+    commands.push(structure.final_command().to_string());
+    let mut model = Model::new(structure);
+    let mut queries = Vec::new();
+    let mut lines = Vec::new();
+    for (idx, command) in commands.iter().enumerate() {
+        if let Some(line) = model.execute(command) {
+            queries.push(idx);
+            lines.push(line);
+        }
+    }
+    Test::new(Script { structure, commands, queries }, ScriptOutput { lines })
+}
+
+fn random_command(rng: &mut dyn RngCore, structure: Structure, keys: &[String], values: std::ops::Range<i64>) -> String {
+    let value = rng.gen_range(values);
+    match structure {
+        Structure::Map => {
+            let key = keys.choose(rng).unwrap();
+            match rng.gen_range(0..10) {
+                0..=3 => format!("insert {} {}", key, value),
+                4..=5 => format!("delete {}", key),
+                6..=8 => format!("get {}", key),
+                _ => "size".to_string(),
+            }
+        }
+        Structure::List => match rng.gen_range(0..12) {
+            0..=2 => format!("push_front {}", value),
+            3..=5 => format!("push_back {}", value),
+            6 => "pop_front".to_string(),
+            7 => "pop_back".to_string(),
+            8 => format!("remove {}", value),
+            9 => "size".to_string(),
+            _ => "print".to_string(),
+        },
+        Structure::Heap => match rng.gen_range(0..10) {
+            0..=4 => format!("push {}", value),
+            5..=6 => "pop".to_string(),
+            7..=8 => "top".to_string(),
+            _ => "size".to_string(),
+        },
+    }
+}
+
+pub struct StructureSupplier {
+    structure: Structure,
+    scale: f64,
+}
+
+impl StructureSupplier {
+    pub fn new(structure: Structure, scale: f64) -> StructureSupplier {
+        StructureSupplier { structure, scale }
+    }
+
+    fn hand_made(&self, node: &mut TestsNode<Script, ScriptOutput>) {
+        let script = |a: &[&str]| make_test(self.structure, a.iter().map(|a| a.to_string()).collect());
+        match self.structure {
+            Structure::Map => {
+                node.child("empty")
+                    .add_test(script(&[]))
+                    .add_test(script(&["get missing", "delete missing", "size"]));
+                node.child("update")
+                    .add_test(script(&["insert a 1", "insert a 2", "get a", "size"]))
+                    .add_test(script(&["insert a 1", "delete a", "get a", "insert a 3", "get a"]));
+                node.child("negative values")
+                    .add_test(script(&["insert key -5", "insert other 0", "get key", "get other"]));
+            }
+            Structure::List => {
+                node.child("empty")
+                    .add_test(script(&[]))
+                    .add_test(script(&["pop_front", "pop_back", "remove 1", "size"]));
+                node.child("single element")
+                    .add_test(script(&["push_back 1", "pop_front", "print"]))
+                    .add_test(script(&["push_front 1", "pop_back", "push_back 2", "print"]));
+                node.child("remove first occurrence")
+                    .add_test(script(&["push_back 1", "push_back 2", "push_back 1", "remove 1", "print"]));
+            }
+            Structure::Heap => {
+                node.child("empty")
+                    .add_test(script(&[]))
+                    .add_test(script(&["pop", "top", "size"]));
+                node.child("duplicates")
+                    .add_test(script(&["push 2", "push 2", "push 1", "pop", "pop", "pop", "pop"]));
+                node.child("negative values")
+                    .add_test(script(&["push -1", "push -10", "push 5", "top", "pop", "top"]));
+            }
+        }
+    }
+
+    fn random_group(&self, node: &mut TestsNode<Script, ScriptOutput>, rng: &mut dyn RngCore,
+                    num: usize, length: std::ops::Range<usize>, key_count: usize) {
+        for _ in 0..scale_count(num, self.scale) {
+            let keys: Vec<String> = (0..key_count)
+                .map(|_| (0..rng.gen_range(1..8)).map(|_| rng.gen_range(b'a'..=b'z') as char).collect())
+                .collect();
+            let commands = (0..rng.gen_range(length.clone()))
+                .map(|_| random_command(rng, self.structure, &keys, -1000..1000))
+                .collect();
+            node.add_test(make_test(self.structure, commands));
+        }
+    }
+}
+
+impl TestsSupplier<Script, ScriptOutput> for StructureSupplier {
+    fn supply_tests(&self, rng: &mut dyn RngCore) -> TestsNode<Script, ScriptOutput> {
+        let mut result = TestsNode::new("root");
+
+        self.hand_made(result.child("hand-made"));
+        self.random_group(result.child("short scripts"), rng, 100, 1..20, 5);
+        self.random_group(result.child("medium scripts"), rng, 50, 50..300, 30);
+        self.random_group(result.child("long scripts"), rng, 10, 2000..10000, 500);
+
+        result
+    }
+}
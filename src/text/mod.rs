@@ -0,0 +1,285 @@
+use std::fmt::{Display, Formatter};
+
+use rand::{Rng, RngCore};
+use rand::seq::SliceRandom;
+
+use crate::base::runner::{TryFromVerbal, VerbalInput};
+use crate::base::scale::scale_count;
+use crate::base::tests_supply::{Test, TestsNode, TestsSupplier};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Task {
+    /// `<lines> <words> <chars> <bytes>` like `wc`, words are split by ASCII whitespace
+    WordCount,
+    /// `yes` or `no` for every line, compared by code points
+    Palindrome,
+    /// Every line with its code points in reverse order
+    Reverse,
+    /// Token count and then every whitespace separated token on its own line
+    Tokens,
+}
+
+impl Task {
+    fn name(&self) -> &'static str {
+        match self {
+            Task::WordCount => "wc",
+            Task::Palindrome => "palindrome",
+            Task::Reverse => "reverse",
+            Task::Tokens => "tokens",
+        }
+    }
+
+    /// Line based tasks print nothing for an empty text, so they never get one
+    fn allows_empty(&self) -> bool {
+        matches!(self, Task::WordCount | Task::Tokens)
+    }
+}
+
+/// Splits text by `\n` dropping a `\r` before it, a trailing piece without terminator is a line too
+fn text_lines(text: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = text.split('\n').map(|a| a.strip_suffix('\r').unwrap_or(a)).collect();
+    if text.is_empty() || text.ends_with('\n') {
+        lines.pop();
+    }
+    lines
+}
+
+fn is_ascii_space(c: char) -> bool {
+    c.is_ascii_whitespace() || c == '\x0b'
+}
+
+fn tokens(text: &str) -> Vec<&str> {
+    text.split(is_ascii_space).filter(|a| !a.is_empty()).collect()
+}
+
+fn solve(task: Task, text: &str) -> Vec<Vec<u8>> {
+    let lines = text_lines(text);
+    match task {
+        Task::WordCount => vec![format!(
+            "{} {} {} {}", text.matches('\n').count(), tokens(text).len(), text.chars().count(), text.len()
+        ).into_bytes()],
+        Task::Palindrome => lines.iter()
+            .map(|line| {
+                let chars: Vec<char> = line.chars().collect();
+                let palindrome = chars.iter().eq(chars.iter().rev());
+                (if palindrome { "yes" } else { "no" }).as_bytes().to_vec()
+            })
+            .collect(),
+        Task::Reverse => lines.iter()
+            .map(|line| line.chars().rev().collect::<String>().into_bytes())
+            .collect(),
+        Task::Tokens => {
+            let tokens = tokens(text);
+            let mut result = vec![tokens.len().to_string().into_bytes()];
+            result.extend(tokens.iter().map(|a| a.as_bytes().to_vec()));
+            result
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TextInput {
+    task: Task,
+    text: String,
+    expected_lines: usize,
+}
+
+impl Display for TextInput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}\n{}", self.task.name(), self.text.len(), self.text)
+    }
+}
+
+impl VerbalInput for TextInput {
+    fn is_output_complete(&self, output: &[u8]) -> bool {
+        output.iter().filter(|a| **a == b'\n').count() >= self.expected_lines
+    }
+}
+
+/// Kept as raw bytes, so that e.g. a byte-wise reversal of UTF-8 is reported as a wrong answer
+#[derive(Clone, PartialEq, Eq)]
+pub struct TextOutput {
+    lines: Vec<Vec<u8>>,
+}
+
+fn render_bytes(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => s.to_string(),
+        Err(_) => bytes.escape_ascii().to_string(),
+    }
+}
+
+impl TryFromVerbal for TextOutput {
+    fn try_from(s: &str) -> Result<Self, String> {
+        Self::try_from_bytes(s.as_bytes())
+    }
+
+    fn try_from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut lines: Vec<Vec<u8>> = bytes.split(|a| *a == b'\n')
+            .map(|a| a.strip_suffix(b"\r").unwrap_or(a).to_vec())
+            .collect();
+        if bytes.is_empty() || bytes.ends_with(b"\n") {
+            lines.pop();
+        }
+        Ok(TextOutput { lines })
+    }
+}
+
+impl Display for TextOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", render_bytes(line))?;
+        }
+        Ok(())
+    }
+}
+
+fn same_line(task: Task, expected: &[u8], actual: &[u8]) -> bool {
+    match task {
+        Task::WordCount | Task::Palindrome => expected.trim_ascii() == actual.trim_ascii(),
+        _ => expected == actual,
+    }
+}
+
+pub fn check(input: &TextInput, expected: &TextOutput, actual: &TextOutput) -> Result<(), String> {
+    for (idx, expected_line) in expected.lines.iter().enumerate() {
+        let actual_line = match actual.lines.get(idx) {
+            Some(line) => line,
+            None => return Err(format!("Expected {} lines but got {}", expected.lines.len(), actual.lines.len())),
+        };
+        if same_line(input.task, expected_line, actual_line) {
+            continue;
+        }
+        let mut message = format!(
+            "Line {}: expected '{}' but got '{}'", idx + 1, render_bytes(expected_line), render_bytes(actual_line)
+        );
+        if let Err(e) = std::str::from_utf8(actual_line) {
+            message += &format!(" (invalid UTF-8 at byte {})", e.valid_up_to());
+        }
+        return Err(message);
+    }
+    if actual.lines.len() > expected.lines.len() {
+        return Err(format!("Expected {} lines but got {}", expected.lines.len(), actual.lines.len()));
+    }
+    Ok(())
+}
+
+fn make_test(task: Task, text: String) -> Test<TextInput, TextOutput> {
+    let lines = solve(task, &text);
+    Test::new(TextInput { task, text, expected_lines: lines.len() }, TextOutput { lines })
+}
+
+/// Letters of different UTF-8 lengths: ASCII, Cyrillic and Greek, CJK, emoji
+const ALPHABETS: [&[char]; 4] = [
+    &['a', 'b', 'c', 'x', 'y', 'z', 'A', 'Z', '0', '9', '!', ','],
+    &['а', 'б', 'в', 'ё', 'я', 'Ж', 'λ', 'Ω'],
+    &['中', '文', '字', '日', '本'],
+    &['😀', '🦀', '🎉', '👍'],
+];
+
+const SPACES: [char; 4] = [' ', ' ', '\t', '\u{a0}'];
+
+#[derive(Clone, Copy)]
+struct TextShape {
+    /// How many of `ALPHABETS` may be mixed in
+    alphabets: usize,
+    lines: usize,
+    line_length: usize,
+    crlf: bool,
+    empty_line_chance: f64,
+    palindrome_chance: f64,
+}
+
+fn random_line(rng: &mut dyn RngCore, shape: &TextShape) -> String {
+    if rng.gen_bool(shape.empty_line_chance) {
+        return String::new();
+    }
+    let length = rng.gen_range(1..=shape.line_length);
+    let palindrome = rng.gen_bool(shape.palindrome_chance);
+    let half = if palindrome { length.div_ceil(2) } else { length };
+    let mut chars: Vec<char> = (0..half)
+        .map(|_| {
+            if rng.gen_bool(0.15) {
+                *SPACES.choose(rng).unwrap()
+            } else {
+                *ALPHABETS[rng.gen_range(0..shape.alphabets)].choose(rng).unwrap()
+            }
+        })
+        .collect();
+    if palindrome {
+        let mirror: Vec<char> = chars.iter().rev().skip(length % 2).copied().collect();
+        chars.extend(mirror);
+    }
+    chars.into_iter().collect()
+}
+
+fn random_text(rng: &mut dyn RngCore, shape: &TextShape) -> String {
+    let ending = if shape.crlf { "\r\n" } else { "\n" };
+    let mut text = String::new();
+    for _ in 0..rng.gen_range(1..=shape.lines) {
+        text += &random_line(rng, shape);
+        text += ending;
+    }
+    text
+}
+
+pub struct TextSupplier {
+    scale: f64,
+}
+
+impl TextSupplier {
+    pub fn new(scale: f64) -> TextSupplier {
+        TextSupplier { scale }
+    }
+
+    fn hand_made(&self, node: &mut TestsNode<TextInput, TextOutput>) {
+        let tasks = [Task::WordCount, Task::Palindrome, Task::Reverse, Task::Tokens];
+        let mut add = |name: &str, text: &str| {
+            let group = node.child(name);
+            for task in tasks {
+                if !text.is_empty() || task.allows_empty() {
+                    group.add_test(make_test(task, text.to_string()));
+                }
+            }
+        };
+        add("empty text", "");
+        add("empty lines", "\n\n\n");
+        add("no trailing newline", "abc\ncba");
+        add("crlf", "level\r\nhello world\r\n\r\n");
+        add("whitespace only", " \t \n\t\n");
+        add("multi-byte", "привет мир\nшалаш\n中文中\n");
+        add("emoji", "🦀🎉🦀\n👍 ok\n");
+        add("non-ascii space", "a\u{a0}b c\n");
+    }
+
+    fn random_group(&self, node: &mut TestsNode<TextInput, TextOutput>, rng: &mut dyn RngCore, num: usize, shape: TextShape) {
+        let tasks = [Task::WordCount, Task::Palindrome, Task::Reverse, Task::Tokens];
+        for _ in 0..scale_count(num, self.scale) {
+            let task = *tasks.choose(rng).unwrap();
+            node.add_test(make_test(task, random_text(rng, &shape)));
+        }
+    }
+}
+
+impl TestsSupplier<TextInput, TextOutput> for TextSupplier {
+    fn supply_tests(&self, rng: &mut dyn RngCore) -> TestsNode<TextInput, TextOutput> {
+        let mut result = TestsNode::new("root");
+
+        self.hand_made(result.child("hand-made"));
+
+        let ascii = TextShape {
+            alphabets: 1, lines: 20, line_length: 40, crlf: false, empty_line_chance: 0.1, palindrome_chance: 0.3,
+        };
+        self.random_group(result.child("ascii"), rng, 50, ascii);
+        self.random_group(result.child("crlf"), rng, 30, TextShape { crlf: true, ..ascii });
+        self.random_group(result.child("cyrillic"), rng, 50, TextShape { alphabets: 2, ..ascii });
+        self.random_group(result.child("any utf-8"), rng, 50, TextShape { alphabets: 4, ..ascii });
+        self.random_group(result.child("empty lines"), rng, 20, TextShape { alphabets: 4, empty_line_chance: 0.7, ..ascii });
+        self.random_group(
+            result.child("long lines"), rng, 5,
+            TextShape { alphabets: 4, lines: 3, line_length: 100_000, ..ascii },
+        );
+
+        result
+    }
+}